@@ -2,15 +2,14 @@ extern crate fflag_macro;
 use fflag_macro::{ include_fflags, include_fflags_runtime };
 
 include_fflags! {
-    "version": "latest",
-    "flags": {
-        "DebugStudioAssertsAlwaysBreak": "DEBUG_STUDIO_ASSERTS_ALWAYS_BREAK",
-        "HttpPointsReporterUrl": "HTTP_POINTS_REPORTER_URL"
-    }
+    version = "latest";
+
+    DebugStudioAssertsAlwaysBreak as DEBUG_STUDIO_ASSERTS_ALWAYS_BREAK;
+    HttpPointsReporterUrl as HTTP_POINTS_REPORTER_URL;
 }
 
 include_fflags_runtime! {
-    "LuaGcStatsEphemeralCooldownSec": "LUA_GC_STATS_EPHEMERAL_COOLDOWN_SEC"
+    LuaGcStatsEphemeralCooldownSec as LUA_GC_STATS_EPHEMERAL_COOLDOWN_SEC;
 }
 
 fn main() {
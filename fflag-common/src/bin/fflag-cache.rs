@@ -0,0 +1,74 @@
+// lists and prunes the version cache the macros share, run it from the workspace that builds them
+use fflag_common::cache::{self, CacheEntry};
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "usage: fflag-cache list\n       fflag-cache prune <days>";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn days_since(last_used: u64) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64);
+
+    now.saturating_sub(last_used) / (SECONDS_PER_DAY * 1000)
+}
+
+fn print_entries(entries: &[CacheEntry]) {
+    for entry in entries {
+        println!(
+            "{}  {} bytes  last used {} days ago",
+            entry.version,
+            entry.size,
+            days_since(entry.last_used)
+        );
+    }
+}
+
+fn list(dir: &Path) {
+    let entries = cache::entries(dir);
+    let total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    print_entries(&entries);
+    println!("{} versions, {} bytes in {}", entries.len(), total_size, dir.display());
+}
+
+fn prune(dir: &Path, days: &str) -> Result<(), String> {
+    let days: u64 = days.parse().map_err(|_| format!("invalid number of days {:?}", days))?;
+    let max_age = Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY));
+
+    let pruned = cache::prune(dir, max_age).map_err(|e| e.to_string())?;
+
+    print_entries(&pruned);
+    println!("pruned {} versions from {}", pruned.len(), dir.display());
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let dir = match cache::cache_dir() {
+        Some(dir) => dir,
+        None => {
+            eprintln!("no writable cache directory, set FFLAG_CACHE_DIR");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match args.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+        ["list"] => Ok(list(dir)),
+        ["prune", days] => prune(dir, days),
+        _ => Err(String::from(USAGE)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
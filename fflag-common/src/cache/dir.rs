@@ -0,0 +1,88 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// resolved once per process, None means the cache lives in memory only
+static CACHE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+// walks up from the crate being compiled to the root of its workspace, tools started
+// outside of cargo walk up from the current directory instead
+pub fn workspace_root() -> Option<PathBuf> {
+    let manifest_dir = env_path("CARGO_MANIFEST_DIR").or_else(|| env::current_dir().ok())?;
+
+    let workspace_root = manifest_dir
+        .ancestors()
+        .find(|dir| {
+            fs::read_to_string(dir.join("Cargo.toml"))
+                .is_ok_and(|manifest| manifest.contains("[workspace]"))
+        })
+        .unwrap_or(&manifest_dir);
+
+    Some(workspace_root.to_path_buf())
+}
+
+// paths in macro input are relative to the crate being compiled, like include_str!
+pub fn manifest_relative_path(path: &str) -> PathBuf {
+    match env_path("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => manifest_dir.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+fn workspace_target_dir() -> Option<PathBuf> {
+    workspace_root().map(|root| root.join("target"))
+}
+
+fn user_cache_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return env_path("LOCALAPPDATA");
+    }
+
+    if cfg!(target_os = "macos") {
+        return env_path("HOME").map(|home| home.join("Library").join("Caches"));
+    }
+
+    env_path("XDG_CACHE_HOME").or_else(|| env_path("HOME").map(|home| home.join(".cache")))
+}
+
+// FFLAG_CACHE_DIR > CARGO_TARGET_DIR > workspace target dir > per-user cache dir
+fn resolve_cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env_path("FFLAG_CACHE_DIR") {
+        return Some(dir);
+    }
+
+    env_path("CARGO_TARGET_DIR")
+        .or_else(workspace_target_dir)
+        .map(|dir| dir.join("fflags"))
+        .or_else(|| user_cache_dir().map(|dir| dir.join("fflag-macro")))
+}
+
+fn is_writable(dir: &Path) -> bool {
+    if fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+
+    let probe = dir.join(".write-test");
+    let writable = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&probe)
+        .is_ok();
+
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+pub fn cache_dir() -> Option<&'static PathBuf> {
+    CACHE_DIR
+        .get_or_init(|| resolve_cache_dir().filter(|dir| is_writable(dir)))
+        .as_ref()
+}
@@ -0,0 +1,155 @@
+use super::lock::write_atomic;
+use crate::error::CacheError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const INDEX_FILE: &str = "index.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CacheEntry {
+    pub version: String,
+    pub file: String,
+    pub size: u64,
+    pub last_used: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CacheIndex {
+    pub entries: Vec<CacheEntry>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as u64)
+}
+
+impl CacheIndex {
+    // entries are still found by file name, so an unreadable index is just rebuilt
+    pub fn load(dir: &Path) -> CacheIndex {
+        fs::read_to_string(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), CacheError> {
+        let path = dir.join(INDEX_FILE);
+        let text = serde_json::to_string_pretty(self).map_err(|e| CacheError::Corrupt {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+
+        write_atomic(&path, text.as_bytes())
+    }
+
+    pub fn touch(&mut self, version: &str, file: &str, size: u64) {
+        let last_used = now();
+
+        match self.entries.iter_mut().find(|entry| entry.version == version) {
+            Some(entry) => {
+                entry.file = String::from(file);
+                entry.size = size;
+                entry.last_used = last_used;
+            }
+            None => self.entries.push(CacheEntry {
+                version: String::from(version),
+                file: String::from(file),
+                size,
+                last_used,
+            }),
+        }
+    }
+
+    // drops least recently used entries until the index fits the limits, the most recently used
+    // one always stays so a version bigger than the limit isn't dumped again on every build
+    pub fn evict(&mut self, max_size: u64, max_entries: usize) -> Vec<CacheEntry> {
+        self.entries.sort_by_key(|entry| Reverse(entry.last_used));
+
+        let mut total_size = 0;
+        let mut kept = 0;
+
+        let (keep, evicted): (Vec<CacheEntry>, Vec<CacheEntry>) =
+            self.entries.drain(..).partition(|entry| {
+                total_size += entry.size;
+                kept += 1;
+
+                kept == 1 || (total_size <= max_size && kept <= max_entries)
+            });
+
+        self.entries = keep;
+        evicted
+    }
+
+    // drops entries that weren't used within `max_age`
+    pub fn prune(&mut self, max_age: Duration) -> Vec<CacheEntry> {
+        let cutoff = now().saturating_sub(max_age.as_millis() as u64);
+
+        let (keep, pruned): (Vec<CacheEntry>, Vec<CacheEntry>) =
+            self.entries.drain(..).partition(|entry| entry.last_used >= cutoff);
+
+        self.entries = keep;
+        pruned
+    }
+
+    pub fn contains_file(&self, file: &str) -> bool {
+        self.entries.iter().any(|entry| entry.file == file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version: &str, size: u64, last_used: u64) -> CacheEntry {
+        CacheEntry {
+            version: String::from(version),
+            file: format!("{}.bin", version),
+            size,
+            last_used,
+        }
+    }
+
+    fn versions(entries: &[CacheEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.version.as_str()).collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut index = CacheIndex {
+            entries: vec![entry("old", 10, 1), entry("new", 10, 3), entry("mid", 10, 2)],
+        };
+
+        let evicted = index.evict(20, usize::MAX);
+
+        assert_eq!(versions(&index.entries), ["new", "mid"]);
+        assert_eq!(versions(&evicted), ["old"]);
+    }
+
+    #[test]
+    fn keeps_an_oversized_newest_entry() {
+        let mut index = CacheIndex {
+            entries: vec![entry("old", 10, 1), entry("huge", 100, 2)],
+        };
+
+        let evicted = index.evict(50, 0);
+
+        assert_eq!(versions(&index.entries), ["huge"]);
+        assert_eq!(versions(&evicted), ["old"]);
+    }
+
+    #[test]
+    fn prunes_by_age() {
+        let mut index = CacheIndex {
+            entries: vec![entry("stale", 10, 0), entry("fresh", 10, now())],
+        };
+
+        let pruned = index.prune(Duration::from_secs(60));
+
+        assert_eq!(versions(&index.entries), ["fresh"]);
+        assert_eq!(versions(&pruned), ["stale"]);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::CacheError;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+
+// advisory lock shared between every rustc process using the same cache directory,
+// released when dropped
+pub struct CacheLock {
+    _file: Option<File>,
+}
+
+impl CacheLock {
+    pub fn none() -> Self {
+        CacheLock { _file: None }
+    }
+
+    pub fn exclusive(path: &Path) -> Result<Self, CacheError> {
+        let file = open_lock_file(path)?;
+        file.lock().map_err(|e| lock_error(path, e))?;
+
+        Ok(CacheLock { _file: Some(file) })
+    }
+
+    // None when another process holds the lock
+    pub fn try_exclusive(path: &Path) -> Result<Option<Self>, CacheError> {
+        let file = open_lock_file(path)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(CacheLock { _file: Some(file) })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(lock_error(path, e)),
+        }
+    }
+}
+
+fn lock_error(path: &Path, e: std::io::Error) -> CacheError {
+    CacheError::Io {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    }
+}
+
+fn open_lock_file(path: &Path) -> Result<File, CacheError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| lock_error(path, e))?;
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| lock_error(path, e))
+}
+
+// readers never observe a partially written file, they either see the old or the new contents
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), CacheError> {
+    let tmp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+    let write_error = |e: std::io::Error| CacheError::Io {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    };
+
+    fs::write(&tmp_path, data).map_err(write_error)?;
+
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        write_error(e)
+    })
+}
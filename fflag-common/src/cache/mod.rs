@@ -0,0 +1,128 @@
+mod dir;
+mod index;
+mod lock;
+
+use crate::config::env_value;
+use crate::error::CacheError;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub use dir::{cache_dir, manifest_relative_path, workspace_root};
+pub use index::{CacheEntry, CacheIndex};
+pub use lock::{write_atomic, CacheLock};
+
+pub const VERSIONS_DIR: &str = "versions";
+pub const LOCKS_DIR: &str = "locks";
+const INDEX_LOCK: &str = "index.lock";
+const DEFAULT_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+pub fn cache_io_error(path: &Path, e: std::io::Error) -> CacheError {
+    CacheError::Io {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    }
+}
+
+// versions are hashes in practice, but keep the file name safe for anything else
+pub fn entry_file_name(version: &str) -> String {
+    let name: String = version
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+
+    format!("{}.bin", name)
+}
+
+// held while a version is looked up and dumped, so parallel builds dump each version once
+fn entry_lock_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(LOCKS_DIR).join(file_name).with_extension("lock")
+}
+
+pub fn lock_version(dir: &Path, version: &str) -> Result<CacheLock, CacheError> {
+    CacheLock::exclusive(&entry_lock_path(dir, &entry_file_name(version)))
+}
+
+fn lock_index(dir: &Path) -> Result<CacheLock, CacheError> {
+    CacheLock::exclusive(&dir.join(LOCKS_DIR).join(INDEX_LOCK))
+}
+
+fn remove_entry_file(path: &Path) -> Result<(), CacheError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(cache_io_error(path, e)),
+        _ => Ok(()),
+    }
+}
+
+// marks a version as used, evicting entries past FFLAG_CACHE_MAX_SIZE/FFLAG_CACHE_MAX_ENTRIES
+pub fn update_index(dir: &Path, version: &str, file_name: &str, size: u64) -> Result<(), CacheError> {
+    let _lock = lock_index(dir)?;
+
+    let mut index = CacheIndex::load(dir);
+    index.touch(version, file_name, size);
+
+    let max_size = env_value("FFLAG_CACHE_MAX_SIZE").unwrap_or(DEFAULT_MAX_SIZE);
+    let max_entries = env_value("FFLAG_CACHE_MAX_ENTRIES").unwrap_or(usize::MAX);
+
+    for entry in index.evict(max_size, max_entries) {
+        remove_entry_file(&dir.join(VERSIONS_DIR).join(&entry.file))?;
+    }
+
+    index.save(dir)
+}
+
+// the cached versions, most recently used first
+pub fn entries(dir: &Path) -> Vec<CacheEntry> {
+    let mut index = CacheIndex::load(dir);
+    index.evict(u64::MAX, usize::MAX);
+
+    index.entries
+}
+
+// removes `file_name` unless a build holds its lock, returns whether it was removed
+fn remove_unlocked(dir: &Path, file_name: &str) -> Result<bool, CacheError> {
+    match CacheLock::try_exclusive(&entry_lock_path(dir, file_name))? {
+        Some(_lock) => remove_entry_file(&dir.join(VERSIONS_DIR).join(file_name)).map(|_| true),
+        None => Ok(false),
+    }
+}
+
+// drops the versions that weren't used within `max_age`, along with files under versions/ that
+// are that old and missing from the index, versions a running build has locked are left alone
+pub fn prune(dir: &Path, max_age: Duration) -> Result<Vec<CacheEntry>, CacheError> {
+    let _lock = lock_index(dir)?;
+
+    let mut index = CacheIndex::load(dir);
+    let mut pruned = Vec::new();
+
+    for entry in index.prune(max_age) {
+        match remove_unlocked(dir, &entry.file)? {
+            true => pruned.push(entry),
+            false => index.entries.push(entry),
+        }
+    }
+
+    // a file written by another build that hasn't made it into the index yet is brand new
+    let versions_dir = dir.join(VERSIONS_DIR);
+    let files = match fs::read_dir(&versions_dir) {
+        Ok(files) => files,
+        Err(e) if e.kind() == ErrorKind::NotFound => return index.save(dir).map(|_| pruned),
+        Err(e) => return Err(cache_io_error(&versions_dir, e)),
+    };
+
+    for file in files.flatten() {
+        let name = file.file_name().to_string_lossy().into_owned();
+        let age = file
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+
+        if !index.contains_file(&name) && age.is_some_and(|age| age >= max_age) {
+            remove_unlocked(dir, &name)?;
+        }
+    }
+
+    index.save(dir).map(|_| pruned)
+}
@@ -0,0 +1,11 @@
+use std::env;
+use std::str::FromStr;
+
+// every numeric FFLAG_* setting is read through this, unset, empty and unparsable values mean the default
+pub fn env_value<T: FromStr>(key: &str) -> Option<T> {
+    env::var(key)
+        .ok()
+        .map(|value| String::from(value.trim()))
+        .filter(|value| !value.is_empty())
+        .and_then(|value| value.parse().ok())
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum HttpError {
+    Network { url: String, reason: String },
+    InvalidResponse { url: String, reason: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Network { url, reason } => {
+                write!(f, "Request to {} failed: {}", url, reason)
+            }
+            HttpError::InvalidResponse { url, reason } => {
+                write!(f, "Invalid response from {}: {}", url, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io { path: PathBuf, reason: String },
+    Corrupt { path: PathBuf, reason: String },
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io { path, reason } => {
+                write!(f, "Failed to access cache {}: {}", path.display(), reason)
+            }
+            CacheError::Corrupt { path, reason } => {
+                write!(f, "Cache {} is corrupt: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
@@ -0,0 +1,135 @@
+use crate::config::env_value;
+use crate::error::HttpError;
+use attohttpc::header::{RANGE, USER_AGENT};
+use attohttpc::{ErrorKind, ProxySettings, RequestBuilder, Response};
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_USER_AGENT: &str = concat!("fflag-macro/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+const DEFAULT_READ_TIMEOUT: u64 = 60;
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_MAX_RESPONSE_SIZE: u64 = 64 << 20;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+// every request goes through this, tuned with the FFLAG_HTTP_* env vars
+struct HttpConfig {
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    retries: u32,
+    user_agent: String,
+}
+
+impl HttpConfig {
+    fn from_env() -> Self {
+        HttpConfig {
+            connect_timeout: Duration::from_secs(
+                env_value("FFLAG_HTTP_CONNECT_TIMEOUT").unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            ),
+            read_timeout: Duration::from_secs(env_value("FFLAG_HTTP_READ_TIMEOUT").unwrap_or(DEFAULT_READ_TIMEOUT)),
+            retries: env_value("FFLAG_HTTP_RETRIES").unwrap_or(DEFAULT_RETRIES).min(16),
+            user_agent: env_value("FFLAG_HTTP_USER_AGENT").unwrap_or_else(|| String::from(DEFAULT_USER_AGENT)),
+        }
+    }
+
+    // HTTPS_PROXY, HTTP_PROXY, ALL_PROXY and NO_PROXY are read the same way curl reads them
+    fn request(&self, url: &str) -> RequestBuilder {
+        attohttpc::get(url)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .header(USER_AGENT, self.user_agent.as_str())
+            .proxy_settings(ProxySettings::from_env())
+    }
+}
+
+// connection failures and cut off responses are worth another try, bad urls and tls errors aren't
+fn is_transient(e: &attohttpc::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::Io(_) | ErrorKind::ConnectError { .. } | ErrorKind::InvalidResponse(_)
+    )
+}
+
+// sends a GET to `url` and hands the response to `read`, retrying with exponential
+// backoff when the connection fails or the server answers with a 5xx
+pub fn get<T, F>(url: &str, read: F) -> Result<T, HttpError>
+where
+    F: FnMut(Response) -> attohttpc::Result<T>,
+{
+    send(url, None, read)
+}
+
+// reads a text response of at most FFLAG_MAX_RESPONSE_SIZE bytes, so a broken server can't exhaust memory
+pub fn get_text(url: &str) -> Result<String, HttpError> {
+    let max_size = env_value("FFLAG_MAX_RESPONSE_SIZE").unwrap_or(DEFAULT_MAX_RESPONSE_SIZE);
+    let invalid_response = |reason: String| HttpError::InvalidResponse {
+        url: String::from(url),
+        reason,
+    };
+
+    let data = get(url, |res| {
+        let mut data = Vec::new();
+        res.take(max_size.saturating_add(1)).read_to_end(&mut data)?;
+
+        Ok(data)
+    })?;
+
+    if data.len() as u64 > max_size {
+        return Err(invalid_response(format!(
+            "response is larger than the {} bytes allowed by FFLAG_MAX_RESPONSE_SIZE",
+            max_size
+        )));
+    }
+
+    String::from_utf8(data).map_err(|e| invalid_response(e.to_string()))
+}
+
+// like `get`, but asks for the bytes `start..end` only, servers are free to ignore that and answer with a 200
+pub fn get_range<T, F>(url: &str, start: u64, end: u64, read: F) -> Result<T, HttpError>
+where
+    F: FnMut(Response) -> attohttpc::Result<T>,
+{
+    send(url, Some(format!("bytes={}-{}", start, end.saturating_sub(1))), read)
+}
+
+fn send<T, F>(url: &str, range: Option<String>, mut read: F) -> Result<T, HttpError>
+where
+    F: FnMut(Response) -> attohttpc::Result<T>,
+{
+    let config = HttpConfig::from_env();
+    let mut attempt = 0;
+
+    loop {
+        let mut request = config.request(url);
+        if let Some(range) = &range {
+            request = request.header(RANGE, range.as_str());
+        }
+
+        let result = match request.send() {
+            Ok(res) if res.status().is_server_error() => Err((true, format!("server returned {}", res.status()))),
+            Ok(res) if !res.is_success() => Err((false, format!("server returned {}", res.status()))),
+            Ok(res) => read(res).map_err(|e| (is_transient(&e), e.to_string())),
+            Err(e) => Err((is_transient(&e), e.to_string())),
+        };
+
+        match result {
+            Ok(value) => return Ok(value),
+            Err((true, _)) if attempt < config.retries => {
+                thread::sleep(BASE_BACKOFF * 2u32.pow(attempt));
+                attempt += 1;
+            }
+            Err((_, reason)) => {
+                let reason = match attempt {
+                    0 => reason,
+                    attempt => format!("{} (gave up after {} retries)", reason, attempt),
+                };
+
+                return Err(HttpError::Network {
+                    url: String::from(url),
+                    reason,
+                });
+            }
+        }
+    }
+}
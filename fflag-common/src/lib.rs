@@ -0,0 +1,7 @@
+// settings and helpers shared by fflag-macro, fflag-proc-macro and the fflag-cache tool
+pub mod cache;
+pub mod config;
+pub mod error;
+pub mod http;
+
+pub use error::{CacheError, HttpError};
@@ -1,6 +1,14 @@
+use crate::error::FFlagError;
+use crate::limits;
+use crate::manifest::{Manifest, Package};
+use crate::mapped::{self, MappedFile};
+use crate::zipdir;
+use attohttpc::StatusCode;
+use fflag_common::http;
 use serde::Deserialize;
-use std::error::Error;
-use std::io::{self, Cursor, Read};
+use std::env;
+use std::io::{BufReader, Read, Seek};
+use zip::result::ZipError;
 use zip::ZipArchive;
 
 #[derive(Deserialize)]
@@ -9,29 +17,211 @@ struct VersionData {
     client_version_upload: String,
 }
 
-const VERSION_API: &str =
-    "https://clientsettings.roblox.com/v2/client-version/WindowsStudio64/channel/LIVE";
+const VERSION_API: &str = "https://clientsettings.roblox.com";
+const SETUP_CDN: &str = "https://setup.rbxcdn.com";
 
-pub fn get_latest_version<'a>() -> Result<String, Box<dyn Error>> {
-    let res = attohttpc::get(VERSION_API).send()?;
-    let data: VersionData = serde_json::from_str(&res.text()?)?;
+const BINARY_NAME: &str = "RobloxStudioBeta.exe";
+const STUDIO_PACKAGE: &str = "RobloxStudio.zip";
+
+const LIVE_CHANNEL: &str = "LIVE";
+
+// base urls of the roblox endpoints, so a mirror or a local test server can stand in for them
+pub struct Endpoints {
+    pub version_api: String,
+    pub setup_cdn: String,
+    pub channel: String,
+}
+
+fn setting(key: Option<String>, var: &str) -> Option<String> {
+    key.or_else(|| env::var(var).ok().filter(|value| !value.is_empty()))
+}
+
+fn base_url(key: Option<String>, var: &str, default: &str) -> String {
+    let url = setting(key, var).unwrap_or_else(|| String::from(default));
+
+    String::from(url.trim_end_matches('/'))
+}
+
+impl Endpoints {
+    // macro keys take precedence over the FFLAG_VERSION_API / FFLAG_SETUP_CDN / FFLAG_CHANNEL env vars
+    pub fn new(version_api: Option<String>, setup_cdn: Option<String>, channel: Option<String>) -> Self {
+        Endpoints {
+            version_api: base_url(version_api, "FFLAG_VERSION_API", VERSION_API),
+            setup_cdn: base_url(setup_cdn, "FFLAG_SETUP_CDN", SETUP_CDN),
+            channel: setting(channel, "FFLAG_CHANNEL").unwrap_or_else(|| String::from(LIVE_CHANNEL)),
+        }
+    }
+
+    fn is_live(&self) -> bool {
+        self.channel.eq_ignore_ascii_case(LIVE_CHANNEL)
+    }
+
+    // versions, specs and cache entries of other channels are suffixed with the channel so they never mix with LIVE
+    pub fn channel_key(&self, key: &str) -> String {
+        match self.is_live() {
+            true => String::from(key),
+            false => format!("{}@{}", key, self.channel.to_lowercase()),
+        }
+    }
+
+    fn version_url(&self) -> String {
+        format!("{}/v2/client-version/WindowsStudio64/channel/{}", self.version_api, self.channel)
+    }
+
+    // deployments outside of LIVE live under /channel/<name>/ on the setup cdn
+    fn setup_url(&self, file: &str) -> String {
+        match self.is_live() {
+            true => format!("{}/{}", self.setup_cdn, file),
+            false => format!("{}/channel/{}/{}", self.setup_cdn, self.channel.to_lowercase(), file),
+        }
+    }
+
+    fn package_url(&self, version: &str, package: &str) -> String {
+        self.setup_url(&format!("{}-{}", version, package))
+    }
+}
+
+pub fn get_latest_version(endpoints: &Endpoints) -> Result<String, FFlagError> {
+    let url = endpoints.version_url();
+    let text = http::get_text(&url)?;
+
+    let data: VersionData =
+        serde_json::from_str(&text).map_err(|e| FFlagError::InvalidResponse {
+            url,
+            reason: e.to_string(),
+        })?;
 
     Ok(data.client_version_upload)
 }
 
-fn unzip_binary(zip: Vec<u8>) -> Result<Vec<u8>, Box<dyn Error>> {
-    let mut vec = Vec::new();
-    let mut archive = ZipArchive::new(Cursor::new(zip))?;
+fn zip_error<E: ToString>(version: &str, e: E) -> FFlagError {
+    FFlagError::ZipArchive {
+        version: String::from(version),
+        reason: e.to_string(),
+    }
+}
+
+// extracts just the studio executable into a temp file and maps it
+pub fn unzip_binary<R: Read + Seek>(version: &str, zip: R) -> Result<MappedFile, FFlagError> {
+    let mut archive = ZipArchive::new(zip).map_err(|e| zip_error(version, e))?;
+
+    let mut entry = archive.by_name(BINARY_NAME).map_err(|e| match e {
+        ZipError::FileNotFound => FFlagError::ZipEntryMissing {
+            version: String::from(version),
+            entry: String::from(BINARY_NAME),
+        },
+        e => zip_error(version, e),
+    })?;
 
-    let mut file = archive.by_name("RobloxStudioBeta.exe")?;
-    file.read_to_end(&mut vec)?;
+    // the sizes in the archive can lie, so the copy is capped as well
+    let compressed = entry.compressed_size();
+    limits::check_unpacked(BINARY_NAME, entry.size(), compressed)?;
 
-    Ok(vec)
+    let mut file = mapped::temp_file()?;
+    let mut reader = entry.by_ref().take(limits::max_unpacked_size(compressed) + 1);
+    let size = mapped::copy_with(&mut reader, &mut file, |_| Ok(())).map_err(|e| zip_error(version, e))?;
+    limits::check_unpacked(BINARY_NAME, size, compressed)?;
+
+    MappedFile::new(&file).map_err(mapped::temp_file_error)
 }
 
-pub fn get_binary(version: String) -> Result<Vec<u8>, Box<dyn Error>> {
-    let url = format!("http://setup.rbxcdn.com/{}-RobloxStudio.zip", version);
-    let zip = attohttpc::get(url).send()?.bytes()?;
+pub fn get_deploy_history(endpoints: &Endpoints) -> Result<String, FFlagError> {
+    Ok(http::get_text(&endpoints.setup_url("DeployHistory.txt"))?)
+}
+
+pub fn get_manifest(endpoints: &Endpoints, version: &str) -> Result<Manifest, FFlagError> {
+    let url = endpoints.package_url(version, "rbxPkgManifest.txt");
+    let text = http::get_text(&url)?;
+
+    Manifest::parse(version, &text)
+}
+
+// fetches `start..end` of a package, or None when the server doesn't do range requests
+fn get_package_range(url: &str, start: u64, end: u64) -> Result<Option<Vec<u8>>, FFlagError> {
+    let data = http::get_range(url, start, end, |res| {
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        res.take(end - start).read_to_end(&mut data)?;
+
+        Ok(Some(data))
+    })?;
+
+    Ok(data)
+}
+
+// lists the entries of a remote package from its central directory without downloading all of it
+fn get_package_entries(endpoints: &Endpoints, version: &str, package: &Package) -> Result<Option<Vec<String>>, FFlagError> {
+    let url = endpoints.package_url(version, &package.name);
+    let tail_start = package.zip_size.saturating_sub(zipdir::MAX_TAIL_SIZE);
+
+    let Some(tail) = get_package_range(&url, tail_start, package.zip_size)? else {
+        return Ok(None);
+    };
+    let Some((offset, size)) = zipdir::find_directory(&tail) else {
+        return Ok(None);
+    };
+
+    // the directory usually fits in the tail we already have
+    let directory = match offset.checked_sub(tail_start) {
+        Some(start) if start + size <= tail.len() as u64 => tail[start as usize..(start + size) as usize].to_vec(),
+        _ => match get_package_range(&url, offset, offset + size)? {
+            Some(directory) => directory,
+            None => return Ok(None),
+        },
+    };
+
+    Ok(Some(zipdir::entry_names(&directory)))
+}
+
+// the exe has always shipped in RobloxStudio.zip, so that package is checked first, when a
+// server can't list a package it is only trusted if it has that name
+fn find_binary_package<'a>(endpoints: &Endpoints, version: &str, manifest: &'a Manifest) -> Result<&'a Package, FFlagError> {
+    let candidates = manifest
+        .find(STUDIO_PACKAGE)
+        .into_iter()
+        .chain(manifest.packages.iter().filter(|package| {
+            package.name != STUDIO_PACKAGE && package.name.ends_with(".zip")
+        }));
+
+    for package in candidates {
+        match get_package_entries(endpoints, version, package)? {
+            Some(entries) if entries.iter().any(|entry| entry == BINARY_NAME) => return Ok(package),
+            None if package.name == STUDIO_PACKAGE => return Ok(package),
+            _ => {}
+        }
+    }
+
+    Err(FFlagError::BinaryPackageMissing {
+        version: String::from(version),
+        entry: String::from(BINARY_NAME),
+        packages: manifest.packages.iter().map(|package| package.name.clone()).collect(),
+    })
+}
+
+pub fn get_binary(endpoints: &Endpoints, version: String) -> Result<MappedFile, FFlagError> {
+    let manifest = get_manifest(endpoints, &version)?;
+    let package = find_binary_package(endpoints, &version, &manifest)?;
+
+    limits::check_download(&package.name, package.zip_size)?;
+
+    // the package is streamed to disk and hashed on the way instead of being buffered,
+    // and a server sending more than the limit is cut off
+    let url = endpoints.package_url(&version, &package.name);
+    let mut file = mapped::temp_file()?;
+    let (size, md5) = http::get(&url, |res| {
+        let mut md5 = md5::Context::new();
+        let size = mapped::copy_with(&mut res.take(limits::max_download_size() + 1), &mut file, |chunk| {
+            md5.consume(chunk);
+            Ok(())
+        })?;
+
+        Ok((size, format!("{:x}", md5.compute())))
+    })?;
+    limits::check_download(&package.name, size)?;
+    package.verify(size, &md5)?;
 
-    Ok(unzip_binary(zip)?)
+    unzip_binary(&version, BufReader::new(file))
 }
@@ -0,0 +1,163 @@
+use crate::dump::FastVar;
+use crate::error::FFlagError;
+use crate::stream::NetworkStream;
+use std::collections::HashMap;
+
+// magic | format version: u32 | crc32 of the body: u32 | body
+// body: version string | flag count: u32 | flags
+const MAGIC: &[u8; 4] = b"FFLC";
+const HEADER_SIZE: usize = 12;
+
+// bump whenever the serialized layout of FastVar or the way it's dumped changes, entries of
+// other versions are dumped again. 2 stored FInts as i32 where 1 stored them as u32,
+// 3 stopped dropping FString defaults that aren't UTF-8, 4 reads short and empty ones,
+// 5 stops reading them from code that only looks like their initializers
+// and 6 expects short ones to be copied into the string
+pub const FORMAT_VERSION: u32 = 6;
+
+pub fn encode(version: &str, flags: &mut HashMap<String, FastVar>) -> Result<Vec<u8>, FFlagError> {
+    let mut body = NetworkStream::new();
+
+    body.write_string_le::<u32>(version)?;
+    body.write_le::<u32>(flags.len() as u32);
+
+    for flag in flags.values_mut() {
+        body.write(flag)?;
+    }
+
+    let mut stream = NetworkStream::new();
+
+    stream.write_bytes(MAGIC);
+    stream.write_le::<u32>(FORMAT_VERSION);
+    stream.write_le::<u32>(crc32fast::hash(&body.data));
+    stream.write_bytes(&body.data);
+
+    Ok(stream.data)
+}
+
+// returns None when the entry was written by another build or for another version,
+// so the caller dumps the binary again instead of reading garbage
+pub fn decode(data: Vec<u8>, version: &str) -> Option<HashMap<String, FastVar>> {
+    let (cached_version, flags) = decode_entry(data)?;
+
+    if cached_version != version {
+        return None;
+    }
+
+    Some(flags)
+}
+
+pub fn is_entry(data: &[u8]) -> bool {
+    data.len() >= HEADER_SIZE && &data[0..4] == MAGIC
+}
+
+// decodes an entry regardless of the version it was written for
+pub fn decode_entry(data: Vec<u8>) -> Option<(String, HashMap<String, FastVar>)> {
+    if !is_entry(&data) {
+        return None;
+    }
+
+    let mut stream = NetworkStream::from(data);
+    stream.ignore_bytes(MAGIC.len());
+
+    let format_version: u32 = stream.read_le().ok()?;
+    let checksum: u32 = stream.read_le().ok()?;
+
+    if format_version != FORMAT_VERSION || checksum != crc32fast::hash(&stream.data[HEADER_SIZE..]) {
+        return None;
+    }
+
+    decode_body(&mut stream).ok()
+}
+
+fn decode_body(
+    stream: &mut NetworkStream,
+) -> Result<(String, HashMap<String, FastVar>), FFlagError> {
+    let version = stream.read_string_le::<u32>()?;
+
+    let num_flags: u32 = stream.read_le()?;
+    let mut map = HashMap::new();
+
+    for _ in 0..num_flags {
+        let flag: FastVar = stream.read()?;
+
+        map.insert(flag.name.clone(), flag);
+    }
+
+    Ok((version, map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::{FastVarType, FastVarValue, FastVarValueType};
+
+    fn int_flags(values: &[(&str, i32)]) -> HashMap<String, FastVar> {
+        values
+            .iter()
+            .map(|&(name, value)| {
+                let flag = FastVar {
+                    name: String::from(name),
+                    var_type: FastVarType::Static,
+                    value_type: FastVarValueType::Int,
+                    value: FastVarValue::Int(value),
+                };
+
+                (String::from(name), flag)
+            })
+            .collect()
+    }
+
+    fn int_value(flags: &HashMap<String, FastVar>, name: &str) -> i32 {
+        match flags[name].value {
+            FastVarValue::Int(value) => value,
+            _ => panic!("{} is not an FInt", name),
+        }
+    }
+
+    #[test]
+    fn signed_ints_round_trip() {
+        let mut flags = int_flags(&[("FIntNegative", -1), ("FIntZero", 0), ("FIntMax", i32::MAX)]);
+
+        let data = encode("version-test", &mut flags).unwrap();
+        let decoded = decode(data, "version-test").unwrap();
+
+        assert_eq!(int_value(&decoded, "FIntNegative"), -1);
+        assert_eq!(int_value(&decoded, "FIntZero"), 0);
+        assert_eq!(int_value(&decoded, "FIntMax"), i32::MAX);
+    }
+
+    #[test]
+    fn other_format_versions_are_rejected() {
+        for format_version in [FORMAT_VERSION - 1, FORMAT_VERSION + 1] {
+            let mut data = encode("version-test", &mut int_flags(&[])).unwrap();
+            data[4..8].copy_from_slice(&format_version.to_le_bytes());
+
+            assert!(decode(data, "version-test").is_none());
+        }
+    }
+
+    #[test]
+    fn string_defaults_keep_their_bytes() {
+        let mut flags = HashMap::new();
+        for (name, value) in [
+            ("Utf8", FastVarValue::String(String::from("héllo"))),
+            ("Latin1", FastVarValue::Bytes(vec![b'h', 0xE9, b'l', b'l', b'o'])),
+        ] {
+            let flag = FastVar {
+                name: String::from(name),
+                var_type: FastVarType::Static,
+                value_type: FastVarValueType::String,
+                value,
+            };
+
+            flags.insert(String::from(name), flag);
+        }
+
+        let data = encode("version-test", &mut flags).unwrap();
+        let decoded = decode(data, "version-test").unwrap();
+
+        assert!(matches!(&decoded["Utf8"].value, FastVarValue::String(str) if str == "héllo"));
+        assert!(matches!(&decoded["Latin1"].value, FastVarValue::Bytes(bytes) if bytes == b"h\xE9llo"));
+    }
+}
@@ -0,0 +1,109 @@
+pub mod format;
+
+use crate::dump::FastVar;
+use crate::error::FFlagError;
+use fflag_common::cache::{self as store, cache_dir, entry_file_name, write_atomic, VERSIONS_DIR};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub use fflag_common::cache::{manifest_relative_path, workspace_root, CacheLock};
+
+const RESOLVED_DIR: &str = "resolved";
+
+fn cache_io_error(path: &Path, e: std::io::Error) -> FFlagError {
+    store::cache_io_error(path, e).into()
+}
+
+pub fn get_fflags_if_version_cached(
+    version: &str,
+) -> Result<Option<HashMap<String, FastVar>>, FFlagError> {
+    let dir = match cache_dir() {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+
+    let file_name = entry_file_name(version);
+    let path = dir.join(VERSIONS_DIR).join(&file_name);
+
+    let vec = match fs::read(&path) {
+        Ok(vec) => vec,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(cache_io_error(&path, e)),
+    };
+
+    let size = vec.len() as u64;
+    let flags = format::decode(vec, version);
+
+    if flags.is_some() {
+        store::update_index(dir, version, &file_name, size)?;
+    }
+
+    Ok(flags)
+}
+
+pub fn write_flags_to_cache(
+    version: &str,
+    flags: &mut HashMap<String, FastVar>,
+) -> Result<(), FFlagError> {
+    let dir = match cache_dir() {
+        Some(dir) => dir,
+        None => return Ok(()),
+    };
+
+    let data = format::encode(version, flags)?;
+
+    let versions_dir = dir.join(VERSIONS_DIR);
+    fs::create_dir_all(&versions_dir).map_err(|e| cache_io_error(&versions_dir, e))?;
+
+    let file_name = entry_file_name(version);
+    let path = versions_dir.join(&file_name);
+    write_atomic(&path, &data)?;
+
+    Ok(store::update_index(dir, version, &file_name, data.len() as u64)?)
+}
+
+// held while a version is looked up and dumped, so parallel builds dump each version once
+pub fn lock_version(version: &str) -> Result<CacheLock, FFlagError> {
+    match cache_dir() {
+        Some(dir) => Ok(store::lock_version(dir, version)?),
+        None => Ok(CacheLock::none()),
+    }
+}
+
+fn resolved_path(dir: &Path, spec: &str) -> PathBuf {
+    dir.join(RESOLVED_DIR).join(entry_file_name(spec)).with_extension("txt")
+}
+
+pub fn read_resolved(spec: &str) -> Result<Option<String>, FFlagError> {
+    let path = match cache_dir() {
+        Some(dir) => resolved_path(dir, spec),
+        None => return Ok(None),
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(version) => Ok(Some(version)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(cache_io_error(&path, e)),
+    }
+}
+
+// the version a spec last resolved to, crates that expand the spec include this file so they are
+// rebuilt when it moves, which is why it is only rewritten when the version actually changes
+pub fn record_resolved(spec: &str, version: &str) -> Result<Option<PathBuf>, FFlagError> {
+    let path = match cache_dir() {
+        Some(dir) => resolved_path(dir, spec),
+        None => return Ok(None),
+    };
+
+    if read_resolved(spec)?.as_deref() == Some(version) {
+        return Ok(Some(path));
+    }
+
+    let dir = path.parent().unwrap_or(&path);
+    fs::create_dir_all(dir).map_err(|e| cache_io_error(dir, e))?;
+    write_atomic(&path, version.as_bytes())?;
+
+    Ok(Some(path))
+}
@@ -6,31 +6,32 @@ use object::{
     },
     read::pe::ImageNtHeaders,
 };
-use std::error::Error;
+use crate::error::FFlagError;
 
+// section data borrows from the mapped binary instead of being copied out of it
 #[derive(Clone)]
-pub struct Section {
+pub struct Section<'a> {
     pub header: ImageSectionHeader,
-    pub data: Vec<u8>,
+    pub data: &'a [u8],
 }
 
-impl Section {
+impl Section<'_> {
     pub fn get_name(&self) -> String {
         String::from_utf8(self.header.name.to_vec())
             .map_or(String::new(), |s| String::from(s.trim_end_matches("\0")))
     }
 }
 
-pub struct Sections {
-    pub data: Vec<Section>,
+pub struct Sections<'a> {
+    pub data: Vec<Section<'a>>,
 }
 
-impl Sections {
+impl<'a> Sections<'a> {
     pub fn new() -> Self {
         Sections { data: Vec::new() }
     }
 
-    pub fn get_section_by_name<S: Into<String>>(&mut self, name: S) -> Option<Section> {
+    pub fn get_section_by_name<S: Into<String>>(&mut self, name: S) -> Option<Section<'a>> {
         let name: String = name.into();
 
         for section in self.data.iter() {
@@ -43,21 +44,35 @@ impl Sections {
     }
 }
 
-pub fn get_sections_from_binary(binary: Vec<u8>) -> Result<Sections, Box<dyn Error>> {
-    let binary: &[u8] = binary.as_ref();
+fn pe_error(section: Option<String>, e: object::read::Error) -> FFlagError {
+    FFlagError::PeParse {
+        section,
+        reason: e.to_string(),
+    }
+}
+
+pub fn get_sections_from_binary(binary: &[u8]) -> Result<Sections<'_>, FFlagError> {
     let mut sections = Sections::new();
 
-    let dos_header = *ImageDosHeader::parse(binary)?;
+    let dos_header = *ImageDosHeader::parse(binary).map_err(|e| pe_error(None, e))?;
     let mut offset = dos_header.nt_headers_offset().into();
 
-    let (nt_headers, _data_directories) = ImageNtHeaders64::parse(binary, &mut offset)?;
+    let (nt_headers, _data_directories) =
+        ImageNtHeaders64::parse(binary, &mut offset).map_err(|e| pe_error(None, e))?;
     let file_header = nt_headers.file_header();
 
-    for section in file_header.sections(binary, offset)?.iter() {
-        sections.data.push(Section {
+    for section in file_header.sections(binary, offset).map_err(|e| pe_error(None, e))?.iter() {
+        let mut section = Section {
             header: *section,
-            data: section.pe_data(binary)?.to_vec(),
-        })
+            data: &[],
+        };
+
+        section.data = section
+            .header
+            .pe_data(binary)
+            .map_err(|e| pe_error(Some(section.get_name()), e))?;
+
+        sections.data.push(section)
     }
 
     Ok(sections)
@@ -1,11 +1,11 @@
+use crate::error::FFlagError;
 use crate::stream::{NetworkStream, Serialize};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::FromPrimitive;
 use strum_macros::AsRefStr;
-use std::error::Error;
 
 #[repr(u32)]
-#[derive(FromPrimitive, ToPrimitive, Clone, Debug, PartialEq, Copy)]
+#[derive(FromPrimitive, ToPrimitive, Clone, Debug, PartialEq, Copy, serde::Serialize, serde::Deserialize)]
 pub enum FastVarType {
     Invalid = 0x00,
     Static = 0x01,
@@ -19,13 +19,13 @@ pub enum FastVarType {
 }
 
 impl Serialize<FastVarType> for FastVarType {
-    fn read(stream: &mut NetworkStream) -> Result<FastVarType, Box<dyn Error>> {
+    fn read(stream: &mut NetworkStream) -> Result<FastVarType, FFlagError> {
         let var_type = FastVarType::from_u32(stream.read_le()?).map_or(FastVarType::Invalid, |t| t);
 
         Ok(var_type)
     }
 
-    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), FFlagError> {
         stream.write_le(self.clone() as u32);
 
         Ok(())
@@ -34,7 +34,7 @@ impl Serialize<FastVarType> for FastVarType {
 
 
 #[repr(u32)]
-#[derive(FromPrimitive, ToPrimitive, Clone, Debug, PartialEq, Copy, AsRefStr)]
+#[derive(FromPrimitive, ToPrimitive, Clone, Debug, PartialEq, Copy, AsRefStr, serde::Serialize, serde::Deserialize)]
 pub enum FastVarValueType {
     Invalid = 0x00,
 
@@ -61,14 +61,14 @@ impl FastVarValueType {
 }
 
 impl Serialize<FastVarValueType> for FastVarValueType {
-    fn read(stream: &mut NetworkStream) -> Result<FastVarValueType, Box<dyn Error>> {
+    fn read(stream: &mut NetworkStream) -> Result<FastVarValueType, FFlagError> {
         let value_type =
             FastVarValueType::from_u32(stream.read_le()?).map_or(FastVarValueType::Invalid, |t| t);
 
         Ok(value_type)
     }
 
-    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), FFlagError> {
         stream.write_le(self.clone() as u32);
 
         Ok(())
@@ -76,16 +76,36 @@ impl Serialize<FastVarValueType> for FastVarValueType {
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub enum FastVarValue {
     Invalid,
     Log(u16),
     String(String),
-    Int(u32),
+    // an FString default that isn't valid UTF-8, kept as the raw bytes from the binary
+    Bytes(Vec<u8>),
+    Int(#[serde(deserialize_with = "deserialize_int")] i32),
     Flag(bool),
     Uninit,
 }
 
+// snapshots written before FInts were signed hold the u32 bit pattern of negative values
+fn deserialize_int<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+    let value = <i64 as serde::Deserialize>::deserialize(deserializer)?;
+
+    i32::try_from(value)
+        .or_else(|_| u32::try_from(value).map(|value| value as i32))
+        .map_err(|_| serde::de::Error::custom(format!("{} is out of range for an FInt", value)))
+}
+
+impl FastVarValue {
+    pub fn from_string_bytes(bytes: Vec<u8>) -> FastVarValue {
+        match String::from_utf8(bytes) {
+            Ok(str) => FastVarValue::String(str),
+            Err(e) => FastVarValue::Bytes(e.into_bytes()),
+        }
+    }
+}
+
 impl ToString for FastVarValue {
     fn to_string(&self) -> String {
         match self {
@@ -93,6 +113,7 @@ impl ToString for FastVarValue {
             FastVarValue::Log(val) => val.to_string(),
             FastVarValue::Flag(val) => val.to_string(),
             FastVarValue::String(val) => val.to_string(),
+            FastVarValue::Bytes(val) => String::from_utf8_lossy(val).into_owned(),
 
             FastVarValue::Invalid => String::from("INVALID VALUE TYPE"),
             FastVarValue::Uninit => String::from("VALUE NOT IN INITIALIZED MEMORY"),
@@ -101,7 +122,7 @@ impl ToString for FastVarValue {
 }
 
 impl Serialize<FastVarValue> for FastVarValue {
-    fn read(stream: &mut NetworkStream) -> Result<FastVarValue, Box<dyn Error>> {
+    fn read(stream: &mut NetworkStream) -> Result<FastVarValue, FFlagError> {
         let value_type: FastVarValueType = stream.read()?;
 
         let value = match value_type {
@@ -109,14 +130,18 @@ impl Serialize<FastVarValue> for FastVarValue {
             FastVarValueType::Uninit => FastVarValue::Uninit,
             FastVarValueType::Int => FastVarValue::Int(stream.read_le()?),
             FastVarValueType::Flag => FastVarValue::Flag(stream.read_bool()?),
-            FastVarValueType::String => FastVarValue::String(stream.read_string_le::<u32>()?),
+            FastVarValueType::String => {
+                let len: u32 = stream.read_le()?;
+
+                FastVarValue::from_string_bytes(stream.read_bytes(len as usize)?)
+            }
             FastVarValueType::Log => FastVarValue::Log(stream.read_le()?),
         };
 
         Ok(value)
     }
 
-    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), FFlagError> {
         match self.clone() {
             FastVarValue::Invalid => stream.write(&mut FastVarValueType::Invalid)?,
             FastVarValue::Uninit => stream.write(&mut FastVarValueType::Uninit)?,
@@ -133,6 +158,15 @@ impl Serialize<FastVarValue> for FastVarValue {
                 stream.write(&mut FastVarValueType::String)?;
                 stream.write_string_le::<u32>(&str)?;
             }
+            // same layout as a string, reading it back tells the two apart again
+            FastVarValue::Bytes(bytes) => {
+                let len = u32::try_from(bytes.len())
+                    .map_err(|_| FFlagError::StreamStringTooLong { len: bytes.len() })?;
+
+                stream.write(&mut FastVarValueType::String)?;
+                stream.write_le(len);
+                stream.write_bytes(bytes);
+            }
             FastVarValue::Log(log) => {
                 stream.write(&mut FastVarValueType::Log)?;
                 stream.write_le(log);
@@ -144,7 +178,7 @@ impl Serialize<FastVarValue> for FastVarValue {
 }
 
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct FastVar {
     pub name: String,
     pub var_type: FastVarType,
@@ -153,7 +187,7 @@ pub struct FastVar {
 }
 
 impl Serialize<FastVar> for FastVar {
-    fn read(stream: &mut NetworkStream) -> Result<FastVar, Box<dyn Error>> {
+    fn read(stream: &mut NetworkStream) -> Result<FastVar, FFlagError> {
         Ok(FastVar {
             name: stream.read_string_le::<u32>()?,
             var_type: stream.read()?,
@@ -162,7 +196,7 @@ impl Serialize<FastVar> for FastVar {
         })
     }
 
-    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), FFlagError> {
         stream.write_string_le::<u32>(&self.name)?;
         stream.write(&mut self.var_type)?;
         stream.write(&mut self.value_type)?;
@@ -5,18 +5,36 @@ mod scanner;
 
 pub use lib::*;
 
-use std::{collections::HashMap, error::Error, num::Wrapping};
+use std::{collections::HashMap, num::Wrapping};
 
+use crate::error::FFlagError;
 use crate::stream::NetworkStream;
 use binary::Sections;
 use scanner::IDAPat;
 
 const DYN_INIT_PAT: &str =
     "41 B8 ?? ?? ?? ?? 48 8D 15 ?? ?? ?? ?? 48 8D 0D ?? ?? ?? ?? E9 ?? ?? ?? ??";
-const STR_INIT_PAT: &str = "48 83 EC ?? B9 ?? ?? ?? ?? E8 ?? ?? ?? ?? 0F 10 05 ?? ?? ?? ?? 48 C7 05 ?? ?? ?? ?? ?? ?? ?? ??";
 const DYN_INIT_SIZE: usize = 25;
+
+// FString defaults longer than msvc's small string buffer are copied into a heap allocation:
+// sub rsp | mov ecx, capacity | call operator new | movups xmm0, [default] | mov [fvar + 16], size
+const STR_INIT_PAT: &str = "48 83 EC ?? B9 ?? ?? ?? ?? E8 ?? ?? ?? ?? 0F 10 05 ?? ?? ?? ?? 48 C7 05 ?? ?? ?? ?? ?? ?? ?? ??";
 const STR_INIT_SIZE: usize = 32;
 
+// shorter ones are copied into the string itself, which keeps the small string capacity of 15.
+// the size is stored between the load and the copy like above:
+// sub rsp | movups xmm0, [default] | mov [fvar + 16], size | movups [fvar], xmm0 | mov [fvar + 24], 15
+const SSO_STR_INIT_PAT: &str =
+    "48 83 EC ?? 0F 10 05 ?? ?? ?? ?? 48 C7 05 ?? ?? ?? ?? ?? ?? ?? ?? 0F 11 05 ?? ?? ?? ?? 48 C7 05 ?? ?? ?? ?? 0F 00 00 00";
+const SSO_STR_INIT_SIZE: usize = 40;
+const SSO_CAPACITY: usize = 15;
+
+// and empty ones have nothing to copy: sub rsp | mov [fvar + 16], 0 | mov [fvar + 24], 15
+const EMPTY_STR_INIT_PAT: &str = "48 83 EC ?? 48 C7 05 ?? ?? ?? ?? 00 00 00 00 48 C7 05 ?? ?? ?? ?? 0F 00 00 00";
+const EMPTY_STR_INIT_SIZE: usize = 26;
+
+const STR_INIT_PATS: &[&str] = &[STR_INIT_PAT, SSO_STR_INIT_PAT, EMPTY_STR_INIT_PAT];
+
 macro_rules! read_object {
     ($expr:expr) => {
         $expr.get(object::LittleEndian) as usize
@@ -28,84 +46,208 @@ fn calc_instruction_offset(
     addr: usize,
     base_section: usize,
     new_section: usize,
-) -> Result<usize, Box<dyn Error>> {
+) -> Result<usize, FFlagError> {
     let offset: i32 = stream.read_le()?;
 
-    let rva = base_section as usize + addr + stream.read_pointer as usize;
+    let rva = base_section + addr + stream.read_pointer;
     let real_rva = Wrapping(rva) + Wrapping(offset as usize);
 
-    Ok(real_rva.0 - new_section)
+    real_rva.0.checked_sub(new_section).ok_or_else(|| FFlagError::PeParse {
+        section: None,
+        reason: format!(
+            "instruction at {:#x} points at {:#x}, before the section at {:#x}",
+            base_section + addr,
+            real_rva.0,
+            new_section
+        ),
+    })
 }
 
-fn read_fvar_at_addr(data: &Vec<u8>, addr: usize) -> NetworkStream {
-    NetworkStream::from(data[addr..addr + DYN_INIT_SIZE].to_vec())
+// operands decoded from pattern matches can point anywhere, so reads through them are bounds checked
+fn section_slice<'a>(data: &'a [u8], section: &str, start: usize, len: usize) -> Result<&'a [u8], FFlagError> {
+    start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| FFlagError::PeParse {
+            section: Some(String::from(section)),
+            reason: format!("{} bytes at {:#x} are past the end of the section", len, start),
+        })
 }
 
-fn read_cstyle_string(data: &Vec<u8>, addr: usize) -> String {
-    let mut out = String::new();
+fn read_fvar_at_addr(data: &[u8], addr: usize) -> Result<NetworkStream, FFlagError> {
+    Ok(NetworkStream::from(section_slice(data, ".text", addr, DYN_INIT_SIZE)?))
+}
 
-    let mut idx = 0;
-    loop {
-        let byte = data[addr + idx];
-        if byte == 0x00 {
-            break;
-        }
+fn rdata_error(reason: String) -> FFlagError {
+    FFlagError::PeParse {
+        section: Some(String::from(".rdata")),
+        reason,
+    }
+}
+
+fn read_cstyle_string(data: &[u8], addr: usize) -> Result<String, FFlagError> {
+    let bytes = data.get(addr..).unwrap_or_default();
+    let len = bytes
+        .iter()
+        .position(|&byte| byte == 0x00)
+        .ok_or_else(|| rdata_error(format!("string at {:#x} is not null terminated", addr)))?;
+
+    String::from_utf8(bytes[..len].to_vec())
+        .map_err(|e| rdata_error(format!("string at {:#x} is not valid UTF-8: {}", addr, e)))
+}
 
-        out.push(byte as char);
-        idx += 1;
+fn missing_section(section: &str) -> FFlagError {
+    FFlagError::PeParse {
+        section: Some(String::from(section)),
+        reason: String::from("section not found"),
     }
+}
+
+// reads the `mov qword ptr [rip + disp], imm32` that stores the size of an FString,
+// returning the offset of the string in .data along with the size
+fn read_fvar_size(
+    stream: &mut NetworkStream,
+    addr: usize,
+    text_rva: usize,
+    data_rva: usize,
+) -> Result<(usize, usize), FFlagError> {
+    stream.ignore_bytes(3);
+
+    // the displacement is relative to the end of the instruction, 4 bytes past where
+    // calc_instruction_offset thinks it ends, and the size sits 16 bytes into the string
+    let fvar_rva = calc_instruction_offset(stream, addr, text_rva, data_rva + 12)?;
+    let size: u32 = stream.read_le()?;
+
+    Ok((fvar_rva, size as usize))
+}
+
+fn read_fvar_default(rdata_data: &[u8], str_rva: usize, size: usize) -> Result<Vec<u8>, FFlagError> {
+    Ok(section_slice(rdata_data, ".rdata", str_rva, size)?.to_vec())
+}
 
-    out
+// reads the capacity store that follows the size store, which has to target the same string
+fn read_sso_capacity(
+    stream: &mut NetworkStream,
+    addr: usize,
+    text_rva: usize,
+    data_rva: usize,
+    fvar_rva: usize,
+) -> Option<()> {
+    let (capacity_rva, _) = read_fvar_size(stream, addr, text_rva, data_rva).ok()?;
+
+    // the capacity sits 8 bytes after the size
+    (fvar_rva.checked_add(8) == Some(capacity_rva)).then_some(())
+}
+
+fn read_sso_init(
+    text_data: &[u8],
+    addr: usize,
+    text_rva: usize,
+    rdata_rva: usize,
+    data_rva: usize,
+) -> Option<(usize, usize, usize)> {
+    let mut stream = NetworkStream::from(section_slice(text_data, ".text", addr, SSO_STR_INIT_SIZE).ok()?);
+
+    stream.ignore_bytes(7);
+    let str_rva = calc_instruction_offset(&mut stream, addr, text_rva, rdata_rva).ok()?;
+    let (fvar_rva, size) = read_fvar_size(&mut stream, addr, text_rva, data_rva).ok()?;
+
+    // a size that doesn't fit the buffer means this wasn't a string initializer after all
+    if size > SSO_CAPACITY {
+        return None;
+    }
+
+    // and neither was one that copies the default somewhere else
+    stream.ignore_bytes(3);
+    let buffer_rva = calc_instruction_offset(&mut stream, addr, text_rva, data_rva).ok()?;
+    if buffer_rva != fvar_rva {
+        return None;
+    }
+
+    read_sso_capacity(&mut stream, addr, text_rva, data_rva, fvar_rva)?;
+    Some((fvar_rva, str_rva, size))
+}
+
+fn read_empty_init(text_data: &[u8], addr: usize, text_rva: usize, data_rva: usize) -> Option<usize> {
+    let mut stream = NetworkStream::from(section_slice(text_data, ".text", addr, EMPTY_STR_INIT_SIZE).ok()?);
+
+    stream.ignore_bytes(4);
+    let (fvar_rva, _) = read_fvar_size(&mut stream, addr, text_rva, data_rva).ok()?;
+
+    read_sso_capacity(&mut stream, addr, text_rva, data_rva, fvar_rva)?;
+    Some(fvar_rva)
 }
 
 pub fn load_fvar_strings(
     sections: &mut Sections,
-    text_data: &Vec<u8>,
-    rdata_data: &Vec<u8>,
+    text_data: &[u8],
+    rdata_data: &[u8],
     text_rva: usize,
     rdata_rva: usize,
     data_rva: usize,
-) -> Result<HashMap<usize, String>, Box<dyn Error>> {
+) -> Result<HashMap<usize, Vec<u8>>, FFlagError> {
     let mut map = HashMap::new();
 
-    let matches =
+    let heap_matches =
         scanner::scan::<IDAPat, &str>(sections, &IDAPat::new(STR_INIT_PAT), Some(".text"));
+    let sso_matches =
+        scanner::scan::<IDAPat, &str>(sections, &IDAPat::new(SSO_STR_INIT_PAT), Some(".text"));
+    let empty_matches =
+        scanner::scan::<IDAPat, &str>(sections, &IDAPat::new(EMPTY_STR_INIT_PAT), Some(".text"));
+
+    if heap_matches.is_empty() && sso_matches.is_empty() && empty_matches.is_empty() {
+        return Err(FFlagError::PatternNotFound {
+            patterns: STR_INIT_PATS,
+            section: ".text",
+        });
+    }
 
-    for addr in matches {
-        let mut stream = NetworkStream::from(text_data[addr..addr + STR_INIT_SIZE].to_vec());
+    for addr in heap_matches {
+        let mut stream = NetworkStream::from(section_slice(text_data, ".text", addr, STR_INIT_SIZE)?);
 
         stream.ignore_bytes(17);
         let str_rva = calc_instruction_offset(&mut stream, addr, text_rva, rdata_rva)?;
+        let (fvar_rva, size) = read_fvar_size(&mut stream, addr, text_rva, data_rva)?;
 
-        stream.ignore_bytes(3);
-        let fvar_rva = calc_instruction_offset(&mut stream, addr, text_rva, data_rva + 12)?; // this should be a + 16 but its not ???
+        map.insert(fvar_rva, read_fvar_default(rdata_data, str_rva, size)?);
+    }
 
-        let str_size: u32 = stream.read_le()?;
+    // the short and empty patterns are generic enough to match other code,
+    // so a match that doesn't hold together is skipped instead of failing the dump
+    for addr in sso_matches {
+        let Some((fvar_rva, str_rva, size)) = read_sso_init(text_data, addr, text_rva, rdata_rva, data_rva) else {
+            continue;
+        };
 
-        let s = String::from_utf8(rdata_data[str_rva..str_rva + str_size as usize].to_vec())
-            .map_or(String::new(), |s| s);
+        if let Ok(bytes) = read_fvar_default(rdata_data, str_rva, size) {
+            map.insert(fvar_rva, bytes);
+        }
+    }
 
-        map.insert(fvar_rva, s);
+    for addr in empty_matches {
+        if let Some(fvar_rva) = read_empty_init(text_data, addr, text_rva, data_rva) {
+            map.insert(fvar_rva, Vec::new());
+        }
     }
 
     Ok(map)
 }
 
-pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
+pub fn get_fflags(binary: &[u8]) -> Result<Vec<FastVar>, FFlagError> {
     let mut sections = binary::get_sections_from_binary(binary)?;
     let mut vec = Vec::new();
 
     let text_section = sections
         .get_section_by_name(".text")
-        .map_or(Err("failed to find .text"), |t| Ok(t))?;
+        .ok_or_else(|| missing_section(".text"))?;
 
     let rdata_section = sections
         .get_section_by_name(".rdata")
-        .map_or(Err("failed to find .rdata"), |t| Ok(t))?;
+        .ok_or_else(|| missing_section(".rdata"))?;
 
     let data_section = sections
         .get_section_by_name(".data")
-        .map_or(Err("failed to find .data"), |t| Ok(t))?;
+        .ok_or_else(|| missing_section(".data"))?;
 
     let text_data = text_section.data;
     let rdata_data = rdata_section.data;
@@ -119,8 +261,8 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
 
     let strings = load_fvar_strings(
         &mut sections,
-        &text_data,
-        &rdata_data,
+        text_data,
+        rdata_data,
         text_rva,
         rdata_rva,
         data_rva,
@@ -129,8 +271,15 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
     let matches =
         scanner::scan::<IDAPat, &str>(&mut sections, &IDAPat::new(DYN_INIT_PAT), Some(".text"));
 
+    if matches.is_empty() {
+        return Err(FFlagError::PatternNotFound {
+            patterns: &[DYN_INIT_PAT],
+            section: ".text",
+        });
+    }
+
     for addr in matches {
-        let mut stream = read_fvar_at_addr(&text_data, addr);
+        let mut stream = read_fvar_at_addr(text_data, addr)?;
 
         stream.ignore_bytes(2);
         let fvar_type: FastVarType = stream.read()?;
@@ -150,16 +299,16 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
             stream.ignore_bytes(3);
 
             read_cstyle_string(
-                &rdata_data,
+                rdata_data,
                 calc_instruction_offset(&mut stream, addr, text_rva, rdata_rva)?,
-            )
+            )?
         };
 
         let mut fvar_val_type = {
             stream.ignore_bytes(1);
 
             let jmp_rva = calc_instruction_offset(&mut stream, addr, text_rva, text_rva)?;
-            let mut jmp_data = NetworkStream::from(text_data[jmp_rva..jmp_rva + 0x3D].to_vec());
+            let mut jmp_data = NetworkStream::from(section_slice(text_data, ".text", jmp_rva, 0x3D)?);
 
             let inst: u16 = jmp_data.read_be()?;
             let offset = 
@@ -170,14 +319,14 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
             let sub_jmp_rva = calc_instruction_offset(&mut jmp_data, jmp_rva, text_rva, text_rva)?;
 
             let mut sub_jmp_stream =
-                NetworkStream::from(text_data[sub_jmp_rva..sub_jmp_rva + 12].to_vec());
+                NetworkStream::from(section_slice(text_data, ".text", sub_jmp_rva, 12)?);
 
             sub_jmp_stream.ignore_bytes(8);
             sub_jmp_stream.read::<FastVarValueType>()?
         };
 
         let value_size = fvar_val_type.get_size();
-        if value_rva + value_size >= data_size - 1 {
+        if value_rva.saturating_add(value_size) >= data_size.saturating_sub(1) {
             vec.push(FastVar {
                 name: fvar_name,
                 var_type: fvar_type,
@@ -196,7 +345,7 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
 
             FastVarValueType::String => {
                 if let Some(value) = strings.get(&value_rva) {
-                    FastVarValue::String(value.clone())
+                    FastVarValue::from_string_bytes(value.clone())
                 } else {
                     FastVarValue::Uninit
                 }
@@ -215,3 +364,229 @@ pub fn get_fflags(binary: Vec<u8>) -> Result<Vec<FastVar>, Box<dyn Error>> {
 
     Ok(vec)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binary::Section;
+    use object::pe::ImageSectionHeader;
+
+    const TEXT_RVA: usize = 0x1000;
+    const RDATA_RVA: usize = 0x4000;
+    const DATA_RVA: usize = 0x6000;
+
+    // .rdata holding the defaults the fixtures point at
+    const RDATA: &[u8] = b"\0\0\0\0short\0\0\0a default that needs the heap\0";
+    const SHORT_DEFAULT: usize = 4;
+    const HEAP_DEFAULT: usize = 12;
+
+    fn text_sections(text: &[u8]) -> Sections<'_> {
+        let (header, _) = object::pod::from_bytes::<ImageSectionHeader>(&[0; 40]).unwrap();
+        let mut header = *header;
+        header.name = *b".text\0\0\0";
+
+        Sections {
+            data: vec![Section { header, data: text }],
+        }
+    }
+
+    // displacement of a rip relative operand in the instruction ending at `end` of .text
+    fn disp(end: usize, target: usize) -> [u8; 4] {
+        ((target as i64 - (TEXT_RVA + end) as i64) as i32).to_le_bytes()
+    }
+
+    // mov qword ptr [rip + disp], imm32 storing into `field` of the string at `fvar` in .data
+    fn store(text: &mut Vec<u8>, fvar: usize, field: usize, value: u32) {
+        let end = text.len() + 11;
+
+        text.extend_from_slice(&[0x48, 0xC7, 0x05]);
+        text.extend_from_slice(&disp(end, DATA_RVA + fvar + field));
+        text.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn load_default(text: &mut Vec<u8>, default: usize) {
+        let end = text.len() + 7;
+
+        text.extend_from_slice(&[0x0F, 0x10, 0x05]);
+        text.extend_from_slice(&disp(end, RDATA_RVA + default));
+    }
+
+    // movups xmmword ptr [rip + disp], xmm0 into the buffer of the string at `fvar`
+    fn copy_default(text: &mut Vec<u8>, fvar: usize) {
+        let end = text.len() + 7;
+
+        text.extend_from_slice(&[0x0F, 0x11, 0x05]);
+        text.extend_from_slice(&disp(end, DATA_RVA + fvar));
+    }
+
+    fn heap_init(text: &mut Vec<u8>, fvar: usize, default: usize, size: u32) {
+        text.extend_from_slice(&[0x48, 0x83, 0xEC, 0x28, 0xB9, 0x20, 0, 0, 0, 0xE8, 0, 0, 0, 0]);
+        load_default(text, default);
+        store(text, fvar, 16, size);
+        text.extend_from_slice(&[0xCC; 8]);
+    }
+
+    // `copy_fvar` and `capacity_fvar` are the strings the default is copied into and whose
+    // capacity is stored, which are both `fvar` for a real initializer
+    fn sso_init_with(
+        text: &mut Vec<u8>,
+        fvar: usize,
+        copy_fvar: usize,
+        capacity_fvar: usize,
+        default: usize,
+        size: u32,
+    ) {
+        text.extend_from_slice(&[0x48, 0x83, 0xEC, 0x28]);
+        load_default(text, default);
+        store(text, fvar, 16, size);
+        copy_default(text, copy_fvar);
+        store(text, capacity_fvar, 24, 15);
+        text.extend_from_slice(&[0xCC; 8]);
+    }
+
+    fn sso_init(text: &mut Vec<u8>, fvar: usize, default: usize, size: u32) {
+        sso_init_with(text, fvar, fvar, fvar, default, size);
+    }
+
+    fn empty_init_with(text: &mut Vec<u8>, fvar: usize, capacity_fvar: usize) {
+        text.extend_from_slice(&[0x48, 0x83, 0xEC, 0x28]);
+        store(text, fvar, 16, 0);
+        store(text, capacity_fvar, 24, 15);
+        text.extend_from_slice(&[0xCC; 8]);
+    }
+
+    fn empty_init(text: &mut Vec<u8>, fvar: usize) {
+        empty_init_with(text, fvar, fvar);
+    }
+
+    fn load(text: &[u8]) -> Result<HashMap<usize, Vec<u8>>, FFlagError> {
+        load_fvar_strings(&mut text_sections(text), text, RDATA, TEXT_RVA, RDATA_RVA, DATA_RVA)
+    }
+
+    #[test]
+    fn sso_defaults() {
+        let mut text = vec![0xCC; 16];
+        sso_init(&mut text, 0x40, SHORT_DEFAULT, 5);
+
+        let strings = load(&text).unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[&0x40], b"short");
+    }
+
+    #[test]
+    fn empty_defaults() {
+        let mut text = vec![0xCC; 16];
+        empty_init(&mut text, 0x60);
+
+        let strings = load(&text).unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[&0x60], b"");
+    }
+
+    #[test]
+    fn every_initializer_kind() {
+        let mut text = vec![0xCC; 16];
+        heap_init(&mut text, 0x20, HEAP_DEFAULT, 29);
+        sso_init(&mut text, 0x40, SHORT_DEFAULT, 5);
+        empty_init(&mut text, 0x60);
+
+        let strings = load(&text).unwrap();
+
+        assert_eq!(strings.len(), 3);
+        assert_eq!(strings[&0x20], b"a default that needs the heap");
+        assert_eq!(strings[&0x40], b"short");
+        assert_eq!(strings[&0x60], b"");
+    }
+
+    #[test]
+    fn copies_to_another_string_are_ignored() {
+        let mut text = vec![0xCC; 16];
+        sso_init_with(&mut text, 0x40, 0x80, 0x40, SHORT_DEFAULT, 5);
+
+        assert!(load(&text).unwrap().is_empty());
+    }
+
+    // written out instead of built with the helpers above, objdump -D -b binary -mi386:x86-64
+    // --adjust-vma=0x1000 decodes it to the listing on the right
+    #[test]
+    fn sso_initializer_listing() {
+        #[rustfmt::skip]
+        let text = [
+            0x48, 0x83, 0xEC, 0x28,                                     // sub    rsp, 0x28
+            0x0F, 0x10, 0x05, 0xF9, 0x2F, 0x00, 0x00,                   // movups xmm0, [rip + 0x2ff9]   ; 0x4004
+            0x48, 0xC7, 0x05, 0x3A, 0x50, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, // mov qword [rip + 0x503a], 5 ; 0x6050
+            0x0F, 0x11, 0x05, 0x23, 0x50, 0x00, 0x00,                   // movups [rip + 0x5023], xmm0   ; 0x6040
+            0x48, 0xC7, 0x05, 0x30, 0x50, 0x00, 0x00, 0x0F, 0x00, 0x00, 0x00, // mov qword [rip + 0x5030], 15 ; 0x6058
+            0x48, 0x8D, 0x0D, 0xD1, 0x00, 0x00, 0x00,                   // lea    rcx, [rip + 0xd1]      ; destructor
+            0x48, 0x83, 0xC4, 0x28,                                     // add    rsp, 0x28
+            0xE9, 0xC7, 0x01, 0x00, 0x00,                               // jmp    atexit
+        ];
+
+        let strings = load(&text).unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[&0x40], b"short");
+    }
+
+    #[test]
+    fn sso_sizes_past_the_buffer_are_ignored() {
+        let mut text = vec![0xCC; 16];
+        sso_init(&mut text, 0x40, HEAP_DEFAULT, 29);
+
+        assert!(load(&text).unwrap().is_empty());
+    }
+
+    #[test]
+    fn capacity_stores_to_another_string_are_ignored() {
+        let mut text = vec![0xCC; 16];
+        sso_init_with(&mut text, 0x40, 0x40, 0x80, SHORT_DEFAULT, 5);
+        empty_init_with(&mut text, 0x60, 0xA0);
+
+        assert!(load(&text).unwrap().is_empty());
+    }
+
+    #[test]
+    fn out_of_bounds_matches_are_ignored() {
+        let mut text = vec![0xCC; 16];
+        sso_init(&mut text, 0x40, RDATA.len() + 0x100, 5);
+        sso_init(&mut text, 0x50, SHORT_DEFAULT, 5);
+
+        // a store below .data, where the string would start before the section
+        text.extend_from_slice(&[0x48, 0x83, 0xEC, 0x28]);
+        let end = text.len() + 11;
+        text.extend_from_slice(&[0x48, 0xC7, 0x05]);
+        text.extend_from_slice(&disp(end, DATA_RVA));
+        text.extend_from_slice(&0u32.to_le_bytes());
+        store(&mut text, 0, 24, 15);
+
+        let strings = load(&text).unwrap();
+
+        assert_eq!(strings.len(), 1);
+        assert_eq!(strings[&0x50], b"short");
+    }
+
+    #[test]
+    fn targets_before_the_section() {
+        let mut stream = NetworkStream::from((-0x10i32).to_le_bytes());
+
+        assert!(calc_instruction_offset(&mut stream, 0, TEXT_RVA, TEXT_RVA).is_err());
+    }
+
+    #[test]
+    fn slices_past_the_section() {
+        assert!(section_slice(RDATA, ".rdata", RDATA.len() - 4, 8).is_err());
+        assert!(section_slice(RDATA, ".rdata", usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn no_initializers() {
+        let text = vec![0xCC; 64];
+
+        let e = load(&text).unwrap_err();
+
+        assert!(matches!(e, FFlagError::PatternNotFound { patterns, .. } if patterns == STR_INIT_PATS));
+        assert!(e.to_string().contains(SSO_STR_INIT_PAT));
+    }
+}
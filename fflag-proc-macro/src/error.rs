@@ -0,0 +1,138 @@
+use fflag_common::{CacheError, HttpError};
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum FFlagError {
+    Network { url: String, reason: String },
+    InvalidResponse { url: String, reason: String },
+    VersionSpec { spec: String, reason: String },
+
+    ZipArchive { version: String, reason: String },
+    ZipEntryMissing { version: String, entry: String },
+    Manifest { version: String, reason: String },
+    BinaryPackageMissing { version: String, entry: String, packages: Vec<String> },
+    PackageIntegrity { package: String, reason: String },
+    SizeLimit { name: String, reason: String },
+
+    PeParse { section: Option<String>, reason: String },
+    PatternNotFound { patterns: &'static [&'static str], section: &'static str },
+
+    CacheIo { path: PathBuf, reason: String },
+    CacheCorrupt { path: PathBuf, reason: String },
+    Lockfile { path: PathBuf, reason: String },
+    Snapshot { path: PathBuf, reason: String },
+    LocalFile { path: PathBuf, reason: String },
+    TempFile { reason: String },
+    NotCached { key: String },
+
+    StreamOutOfBounds { offset: usize, len: usize, size: usize },
+    StreamInvalidString { offset: usize, reason: String },
+    StreamStringTooLong { len: usize },
+}
+
+impl fmt::Display for FFlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FFlagError::Network { url, reason } => {
+                write!(f, "Request to {} failed: {}", url, reason)
+            }
+            FFlagError::InvalidResponse { url, reason } => {
+                write!(f, "Invalid response from {}: {}", url, reason)
+            }
+            FFlagError::VersionSpec { spec, reason } => {
+                write!(f, "Failed to resolve version {}: {}", spec, reason)
+            }
+
+            FFlagError::ZipArchive { version, reason } => {
+                write!(f, "Failed to read zip archive for {}: {}", version, reason)
+            }
+            FFlagError::ZipEntryMissing { version, entry } => {
+                write!(f, "Zip archive for {} does not contain {}", version, entry)
+            }
+            FFlagError::Manifest { version, reason } => {
+                write!(f, "Invalid package manifest for {}: {}", version, reason)
+            }
+            FFlagError::BinaryPackageMissing { version, entry, packages } => write!(
+                f,
+                "No package of {} contains {}, available packages: {}",
+                version,
+                entry,
+                packages.join(", ")
+            ),
+            FFlagError::PackageIntegrity { package, reason } => {
+                write!(f, "Package {} failed verification: {}", package, reason)
+            }
+            FFlagError::SizeLimit { name, reason } => {
+                write!(f, "{} is too large: {}", name, reason)
+            }
+
+            FFlagError::PeParse { section: Some(section), reason } => {
+                write!(f, "Failed to parse section {} of binary: {}", section, reason)
+            }
+            FFlagError::PeParse { section: None, reason } => {
+                write!(f, "Failed to parse binary headers: {}", reason)
+            }
+            FFlagError::PatternNotFound { patterns, section } => {
+                let patterns: Vec<String> = patterns.iter().map(|pattern| format!("\"{}\"", pattern)).collect();
+                write!(f, "Pattern {} not found in section {}", patterns.join(" or "), section)
+            }
+
+            FFlagError::CacheIo { path, reason } => {
+                write!(f, "Failed to access cache {}: {}", path.display(), reason)
+            }
+            FFlagError::CacheCorrupt { path, reason } => {
+                write!(f, "Cache {} is corrupt: {}", path.display(), reason)
+            }
+            FFlagError::Lockfile { path, reason } => {
+                write!(f, "{}: {}", path.display(), reason)
+            }
+            FFlagError::Snapshot { path, reason } => {
+                write!(f, "Snapshot {}: {}", path.display(), reason)
+            }
+            FFlagError::LocalFile { path, reason } => {
+                write!(f, "Failed to read {}: {}", path.display(), reason)
+            }
+            FFlagError::TempFile { reason } => {
+                write!(f, "Failed to create temp file: {}", reason)
+            }
+            FFlagError::NotCached { key } => write!(
+                f,
+                "{} is not cached yet and IDE mode never downloads or dumps Studio, run `cargo build` once",
+                key
+            ),
+
+            FFlagError::StreamOutOfBounds { offset, len, size } => write!(
+                f,
+                "Out of bounds read of {} bytes at offset {} (stream size {})",
+                len, offset, size
+            ),
+            FFlagError::StreamInvalidString { offset, reason } => {
+                write!(f, "Invalid string at offset {}: {}", offset, reason)
+            }
+            FFlagError::StreamStringTooLong { len } => {
+                write!(f, "String of length {} does not fit its length prefix", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FFlagError {}
+
+impl From<HttpError> for FFlagError {
+    fn from(e: HttpError) -> Self {
+        match e {
+            HttpError::Network { url, reason } => FFlagError::Network { url, reason },
+            HttpError::InvalidResponse { url, reason } => FFlagError::InvalidResponse { url, reason },
+        }
+    }
+}
+
+impl From<CacheError> for FFlagError {
+    fn from(e: CacheError) -> Self {
+        match e {
+            CacheError::Io { path, reason } => FFlagError::CacheIo { path, reason },
+            CacheError::Corrupt { path, reason } => FFlagError::CacheCorrupt { path, reason },
+        }
+    }
+}
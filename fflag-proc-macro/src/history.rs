@@ -0,0 +1,233 @@
+use crate::error::FFlagError;
+
+const STUDIO_DEPLOYMENT: &str = "New Studio64 ";
+
+// one studio deployment from DeployHistory.txt, e.g.
+// New Studio64 version-0123456789abcdef at 3/1/2026 10:15:30 AM, file version: 0, 620, 0, 6200517...Done!
+struct Deployment {
+    version: String,
+    date: (u32, u32, u32),
+    build: Option<[u32; 4]>,
+}
+
+fn spec_error<E: ToString>(spec: &str, e: E) -> FFlagError {
+    FFlagError::VersionSpec {
+        spec: String::from(spec),
+        reason: e.to_string(),
+    }
+}
+
+// `date:`, `build:` and `previous:` versions are looked up in the deploy history
+pub fn is_history_spec(spec: &str) -> bool {
+    ["date:", "build:", "previous:"]
+        .iter()
+        .any(|prefix| spec.starts_with(prefix))
+}
+
+fn leading_number(text: &str) -> Option<u32> {
+    let end = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+
+    text[..end].parse().ok()
+}
+
+fn parse_deployment(line: &str) -> Option<Deployment> {
+    let rest = line.trim().strip_prefix(STUDIO_DEPLOYMENT)?;
+    let (version, rest) = rest.split_once(" at ")?;
+    let (date, rest) = rest.split_once(' ')?;
+
+    let mut date = date.split('/').map(|part| part.parse::<u32>().ok());
+    let (month, day, year) = (date.next()??, date.next()??, date.next()??);
+
+    // older deployments were logged without a file version
+    let build = rest.split_once("file version: ").and_then(|(_, build)| {
+        let parts: Vec<u32> = build
+            .split(',')
+            .take(4)
+            .filter_map(|part| leading_number(part.trim()))
+            .collect();
+
+        parts.try_into().ok()
+    });
+
+    Some(Deployment {
+        version: String::from(version.trim()),
+        date: (year, month, day),
+        build,
+    })
+}
+
+// YYYY-MM-DD
+fn parse_date(spec: &str, date: &str) -> Result<(u32, u32, u32), FFlagError> {
+    let parts: Vec<Option<u32>> = date.split('-').map(|part| part.parse().ok()).collect();
+
+    match parts[..] {
+        [Some(year), Some(month @ 1..=12), Some(day @ 1..=31)] => Ok((year, month, day)),
+        _ => Err(spec_error(spec, format!("invalid date {}, expected YYYY-MM-DD", date))),
+    }
+}
+
+// 0.620.0.6200517, with `x` standing in for any component and missing trailing components matching anything
+fn parse_build(spec: &str, build: &str) -> Result<Vec<Option<u32>>, FFlagError> {
+    let parts = build
+        .split('.')
+        .map(|part| match part {
+            "x" | "*" => Ok(None),
+            part => part.parse().map(Some),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| spec_error(spec, format!("invalid build {}, expected something like 0.620.x", build)))?;
+
+    if parts.len() > 4 {
+        return Err(spec_error(spec, format!("build {} has more than 4 components", build)));
+    }
+
+    Ok(parts)
+}
+
+fn matches_build(pattern: &[Option<u32>], build: &[u32; 4]) -> bool {
+    pattern
+        .iter()
+        .zip(build)
+        .all(|(part, component)| part.is_none_or(|part| part == *component))
+}
+
+// resolves a deploy history spec to the version hash it refers to
+pub fn resolve(spec: &str, history: &str) -> Result<String, FFlagError> {
+    let deployments: Vec<Deployment> = history.lines().filter_map(parse_deployment).collect();
+    if deployments.is_empty() {
+        return Err(spec_error(spec, "DeployHistory.txt lists no Studio64 deployments"));
+    }
+
+    // the history is oldest first, so every lookup walks it backwards
+    let found = match spec.split_once(':') {
+        Some(("date", date)) => {
+            let date = parse_date(spec, date)?;
+            deployments.iter().rev().find(|deployment| deployment.date <= date)
+        }
+        Some(("build", build)) => {
+            let pattern = parse_build(spec, build)?;
+            deployments
+                .iter()
+                .rev()
+                .find(|deployment| deployment.build.is_some_and(|build| matches_build(&pattern, &build)))
+        }
+        Some(("previous", count)) => {
+            let count: usize = count
+                .parse()
+                .map_err(|_| spec_error(spec, format!("invalid count {}", count)))?;
+
+            // the same version is sometimes deployed more than once in a row
+            let mut versions: Vec<&Deployment> = Vec::new();
+            for deployment in deployments.iter().rev() {
+                if versions.last().is_none_or(|last| last.version != deployment.version) {
+                    versions.push(deployment);
+                }
+            }
+
+            versions.get(count).copied()
+        }
+        _ => return Err(spec_error(spec, "expected date:, build: or previous:")),
+    };
+
+    found
+        .map(|deployment| deployment.version.clone())
+        .ok_or_else(|| spec_error(spec, "no matching Studio64 deployment in DeployHistory.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &str = "\
+New Studio64 version-old at 2/28/2019 4:02:11 PM... Done!
+New Client version-client at 3/1/2026 9:00:00 AM, file version: 0, 619, 0, 6190400...Done!
+New Studio64 version-aaa at 3/1/2026 10:15:30 AM, file version: 0, 619, 0, 6190417...Done!
+New Studio64 version-bbb at 3/8/2026 10:20:00 AM, file version: 0, 620, 0, 6200517...Done!
+New Studio64 version-bbb at 3/8/2026 11:45:00 AM, file version: 0, 620, 0, 6200517...Done!
+New Studio64 version-ccc at 3/15/2026 9:05:12 AM, file version: 0, 620, 1, 6200702...Done!
+";
+
+    fn resolve_ok(spec: &str) -> String {
+        resolve(spec, HISTORY).unwrap()
+    }
+
+    fn resolve_err(spec: &str) -> String {
+        resolve(spec, HISTORY).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn deployments() {
+        let deployment = parse_deployment(
+            "New Studio64 version-bbb at 3/8/2026 10:20:00 AM, file version: 0, 620, 0, 6200517...Done!",
+        )
+        .unwrap();
+
+        assert_eq!(deployment.version, "version-bbb");
+        assert_eq!(deployment.date, (2026, 3, 8));
+        assert_eq!(deployment.build, Some([0, 620, 0, 6200517]));
+
+        assert!(parse_deployment("New Client version-client at 3/1/2026 9:00:00 AM").is_none());
+        assert!(parse_deployment("garbage").is_none());
+    }
+
+    #[test]
+    fn deployments_without_a_file_version() {
+        let deployment = parse_deployment("New Studio64 version-old at 2/28/2019 4:02:11 PM... Done!").unwrap();
+
+        assert_eq!(deployment.version, "version-old");
+        assert_eq!(deployment.date, (2019, 2, 28));
+        assert!(deployment.build.is_none());
+    }
+
+    #[test]
+    fn builds() {
+        assert_eq!(parse_build("", "0.620.x.*").unwrap(), [Some(0), Some(620), None, None]);
+        assert_eq!(parse_build("", "0.620").unwrap(), [Some(0), Some(620)]);
+
+        assert!(parse_build("", "0.620.y").is_err());
+        assert!(parse_build("", "0.620.0.1.2").is_err());
+    }
+
+    #[test]
+    fn resolve_by_date() {
+        assert_eq!(resolve_ok("date:2026-03-08"), "version-bbb");
+        assert_eq!(resolve_ok("date:2026-03-14"), "version-bbb");
+        assert_eq!(resolve_ok("date:2026-03-15"), "version-ccc");
+        assert_eq!(resolve_ok("date:2026-03-07"), "version-aaa");
+        assert_eq!(resolve_ok("date:2020-01-01"), "version-old");
+
+        assert!(resolve_err("date:2018-01-01").contains("no matching Studio64 deployment"));
+        assert!(resolve_err("date:2026-13-01").contains("invalid date 2026-13-01"));
+    }
+
+    #[test]
+    fn resolve_by_build() {
+        assert_eq!(resolve_ok("build:0.620.0.6200517"), "version-bbb");
+        assert_eq!(resolve_ok("build:0.620"), "version-ccc");
+        assert_eq!(resolve_ok("build:0.620.x.6200517"), "version-bbb");
+        assert_eq!(resolve_ok("build:0.*.0"), "version-bbb");
+        assert_eq!(resolve_ok("build:0.619.x"), "version-aaa");
+
+        assert!(resolve_err("build:0.618").contains("no matching Studio64 deployment"));
+    }
+
+    #[test]
+    fn resolve_previous() {
+        assert_eq!(resolve_ok("previous:0"), "version-ccc");
+        assert_eq!(resolve_ok("previous:1"), "version-bbb");
+        assert_eq!(resolve_ok("previous:2"), "version-aaa");
+        assert_eq!(resolve_ok("previous:3"), "version-old");
+
+        assert!(resolve_err("previous:4").contains("no matching Studio64 deployment"));
+        assert!(resolve_err("previous:-1").contains("invalid count -1"));
+    }
+
+    #[test]
+    fn empty_history() {
+        let e = resolve("previous:0", "New Client version-client at 3/1/2026 9:00:00 AM").unwrap_err();
+
+        assert!(e.to_string().contains("lists no Studio64 deployments"));
+    }
+}
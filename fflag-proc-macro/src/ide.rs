@@ -0,0 +1,23 @@
+use crate::error::FFlagError;
+use std::env;
+use std::sync::OnceLock;
+
+static IDE_MODE: OnceLock<bool> = OnceLock::new();
+
+// rust-analyzer expands macros in its own proc macro server on every keystroke, which must never
+// wait on a download or a dump, FFLAG_IDE_MODE=1 forces the same for other editors and =0 turns it off
+pub fn is_enabled() -> bool {
+    *IDE_MODE.get_or_init(|| match env::var("FFLAG_IDE_MODE") {
+        Ok(value) if !value.is_empty() => value != "0",
+        _ => env::current_exe().is_ok_and(|exe| {
+            exe.file_name()
+                .is_some_and(|name| name.to_string_lossy().contains("rust-analyzer"))
+        }),
+    })
+}
+
+pub fn not_cached_error(key: &str) -> FFlagError {
+    FFlagError::NotCached {
+        key: String::from(key),
+    }
+}
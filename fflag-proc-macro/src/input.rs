@@ -0,0 +1,419 @@
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::{braced, Ident, LitStr, Token};
+
+// a single `FFlagName as FFLAG_VAR` mapping
+pub struct FlagEntry {
+    pub name: LitStr,
+    pub var: Ident,
+}
+
+const KEYS: &[&str] = &[
+    "version",
+    "source",
+    "binary",
+    "zip",
+    "channel",
+    "version_api",
+    "setup_cdn",
+    "settings_api",
+];
+
+#[derive(Default)]
+pub struct Input {
+    pub version: Option<LitStr>,
+    pub source: Option<LitStr>,
+    pub binary: Option<LitStr>,
+    pub zip: Option<LitStr>,
+    pub channel: Option<LitStr>,
+    pub version_api: Option<LitStr>,
+    pub setup_cdn: Option<LitStr>,
+    pub settings_api: Option<LitStr>,
+    pub flags: Vec<FlagEntry>,
+}
+
+impl Input {
+    pub fn version(&self) -> String {
+        self.version
+            .as_ref()
+            .map_or(String::from("latest"), |version| version.value())
+    }
+
+    pub fn version_span(&self) -> proc_macro2::Span {
+        self.version
+            .as_ref()
+            .map_or(proc_macro2::Span::call_site(), |version| version.span())
+    }
+
+    // include_fflags! { version = "latest"; FFlagName as FFLAG_VAR; }
+    // include_fflags! { "version": "latest", "flags": { "FFlagName": "FFLAG_VAR" } }
+    pub fn parse_static(input: ParseStream) -> syn::Result<Self> {
+        let mut out = Input::default();
+
+        if is_legacy(input) {
+            out.parse_legacy(input, false)?;
+        } else {
+            out.parse_native(input)?;
+        }
+
+        out.validate()?;
+
+        // the settings api is only queried by the generated runtime code
+        if let Some(settings_api) = &out.settings_api {
+            return Err(syn::Error::new(
+                settings_api.span(),
+                "`settings_api` is only used by include_fflags_runtime!",
+            ));
+        }
+
+        Ok(out)
+    }
+
+    // include_fflags_runtime! { FFlagName as FFLAG_VAR; }
+    // include_fflags_runtime! { "FFlagName": "FFLAG_VAR" }
+    pub fn parse_runtime(input: ParseStream) -> syn::Result<Self> {
+        let mut out = Input::default();
+
+        if is_legacy(input) {
+            out.parse_legacy(input, true)?;
+        } else {
+            out.parse_native(input)?;
+        }
+
+        out.validate()?;
+        Ok(out)
+    }
+
+    fn set(&mut self, key: &str, key_span: proc_macro2::Span, value: LitStr) -> syn::Result<()> {
+        let slot = match key {
+            "version" => &mut self.version,
+            "source" => &mut self.source,
+            "binary" => &mut self.binary,
+            "zip" => &mut self.zip,
+            "channel" => &mut self.channel,
+            "version_api" => &mut self.version_api,
+            "setup_cdn" => &mut self.setup_cdn,
+            "settings_api" => &mut self.settings_api,
+            _ => {
+                let keys: Vec<String> = KEYS.iter().map(|key| format!("`{}`", key)).collect();
+
+                return Err(syn::Error::new(
+                    key_span,
+                    format!("unknown key `{}`, expected one of {}", key, keys.join(", ")),
+                ));
+            }
+        };
+
+        if slot.is_some() {
+            return Err(syn::Error::new(key_span, format!("duplicate key `{}`", key)));
+        }
+
+        *slot = Some(value);
+        Ok(())
+    }
+
+    // a local binary or zip already pins the flags, so it can't be combined with another source
+    fn validate(&self) -> syn::Result<()> {
+        let keys = [
+            ("version", &self.version),
+            ("source", &self.source),
+            ("binary", &self.binary),
+            ("zip", &self.zip),
+        ];
+
+        for (key, value) in &keys[2..] {
+            let Some(value) = value else { continue };
+
+            let conflict = keys
+                .iter()
+                .find(|(other, other_value)| other != key && other_value.is_some());
+
+            if let Some((other, _)) = conflict {
+                return Err(syn::Error::new(
+                    value.span(),
+                    format!("`{}` can't be combined with `{}`", key, other),
+                ));
+            }
+        }
+
+        // the channel ends up in urls and cache file names
+        if let Some(channel) = &self.channel {
+            let value = channel.value();
+
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err(syn::Error::new(
+                    channel.span(),
+                    format!("invalid channel \"{}\", expected a name like \"zcanary\"", value),
+                ));
+            }
+        }
+
+        // endpoint overrides are base urls that the api paths get appended to
+        for url in [&self.version_api, &self.setup_cdn, &self.settings_api].into_iter().flatten() {
+            let value = url.value();
+
+            if !value.starts_with("http://") && !value.starts_with("https://") {
+                return Err(syn::Error::new(
+                    url.span(),
+                    format!("expected an http:// or https:// base url, got \"{}\"", value),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_flag(&mut self, name: LitStr, var: Ident) {
+        // legacy invocations used `"": ""` to pad out trailing commas
+        if name.value().is_empty() {
+            return;
+        }
+
+        self.flags.push(FlagEntry { name, var });
+    }
+
+    fn parse_native(&mut self, input: ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            if input.peek(LitStr) {
+                let name: LitStr = input.parse()?;
+                input.parse::<Token![as]>()?;
+
+                self.push_flag(name, input.call(Ident::parse_any)?);
+            } else {
+                let ident = input.call(Ident::parse_any)?;
+
+                if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    self.set(&ident.to_string(), ident.span(), input.parse()?)?;
+                } else {
+                    input.parse::<Token![as]>()?;
+
+                    let name = LitStr::new(&ident.unraw().to_string(), ident.span());
+                    self.push_flag(name, input.call(Ident::parse_any)?);
+                }
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![;]>()?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_legacy(&mut self, input: ParseStream, runtime: bool) -> syn::Result<()> {
+        while !input.is_empty() {
+            let key: LitStr = input.parse()?;
+            input.parse::<Token![:]>()?;
+
+            if runtime {
+                let var: LitStr = input.parse()?;
+                self.push_legacy_flag(key, var)?;
+            } else if key.value() == "flags" {
+                let content;
+                braced!(content in input);
+
+                self.parse_legacy_flags(&content)?;
+            } else {
+                self.set(&key.value(), key.span(), input.parse()?)?;
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_legacy_flags(&mut self, input: ParseStream) -> syn::Result<()> {
+        while !input.is_empty() {
+            let name: LitStr = input.parse()?;
+            input.parse::<Token![:]>()?;
+
+            let var: LitStr = input.parse()?;
+            self.push_legacy_flag(name, var)?;
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(())
+    }
+
+    fn push_legacy_flag(&mut self, name: LitStr, var: LitStr) -> syn::Result<()> {
+        if name.value().is_empty() {
+            return Ok(());
+        }
+
+        let var = var.parse_with(Ident::parse_any)?;
+        self.push_flag(name, var);
+
+        Ok(())
+    }
+}
+
+// legacy invocations are the body of a json object, so they always open with `"key":`
+fn is_legacy(input: ParseStream) -> bool {
+    input.peek(LitStr) && input.peek2(Token![:])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse::Parser;
+
+    fn parse_static(tokens: proc_macro2::TokenStream) -> syn::Result<Input> {
+        Input::parse_static.parse2(tokens)
+    }
+
+    fn parse_runtime(tokens: proc_macro2::TokenStream) -> syn::Result<Input> {
+        Input::parse_runtime.parse2(tokens)
+    }
+
+    fn flags(input: &Input) -> Vec<(String, String)> {
+        input
+            .flags
+            .iter()
+            .map(|flag| (flag.name.value(), flag.var.to_string()))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|&(name, var)| (String::from(name), String::from(var)))
+            .collect()
+    }
+
+    fn error(result: syn::Result<Input>) -> String {
+        match result {
+            Ok(_) => panic!("expected the input to be rejected"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn native_keys_and_flags() {
+        let input = parse_static(quote! {
+            version = "version-123";
+            channel = "zcanary";
+
+            DebugFoo as DEBUG_FOO;
+            "Weird.Name" as WEIRD;
+            r#type as TYPE
+        })
+        .unwrap();
+
+        assert_eq!(input.version(), "version-123");
+        assert_eq!(input.channel.as_ref().unwrap().value(), "zcanary");
+        assert_eq!(
+            flags(&input),
+            pairs(&[("DebugFoo", "DEBUG_FOO"), ("Weird.Name", "WEIRD"), ("type", "TYPE")])
+        );
+    }
+
+    #[test]
+    fn native_defaults_to_latest() {
+        let input = parse_static(quote! { DebugFoo as DEBUG_FOO; }).unwrap();
+
+        assert_eq!(input.version(), "latest");
+        assert_eq!(flags(&input), pairs(&[("DebugFoo", "DEBUG_FOO")]));
+    }
+
+    #[test]
+    fn native_runtime() {
+        let input = parse_runtime(quote! {
+            settings_api = "https://mirror.example";
+            DynThing as DYN_THING;
+        })
+        .unwrap();
+
+        assert_eq!(input.settings_api.as_ref().unwrap().value(), "https://mirror.example");
+        assert_eq!(flags(&input), pairs(&[("DynThing", "DYN_THING")]));
+    }
+
+    #[test]
+    fn legacy_json_body() {
+        let input = parse_static(quote! {
+            "version": "latest",
+            "flags": {
+                "DebugFoo": "DEBUG_FOO",
+                "HttpUrl": "HTTP_URL",
+            },
+        })
+        .unwrap();
+
+        assert_eq!(input.version(), "latest");
+        assert_eq!(flags(&input), pairs(&[("DebugFoo", "DEBUG_FOO"), ("HttpUrl", "HTTP_URL")]));
+    }
+
+    #[test]
+    fn legacy_empty_padding() {
+        let input = parse_static(quote! {
+            "version": "latest",
+            "flags": {
+                "DebugFoo": "DEBUG_FOO",
+                "": ""
+            }
+        })
+        .unwrap();
+
+        assert_eq!(flags(&input), pairs(&[("DebugFoo", "DEBUG_FOO")]));
+    }
+
+    #[test]
+    fn legacy_runtime_flat_map() {
+        let input = parse_runtime(quote! {
+            "DynThing": "DYN_THING",
+            "OtherThing": "OTHER_THING",
+            "": ""
+        })
+        .unwrap();
+
+        assert_eq!(
+            flags(&input),
+            pairs(&[("DynThing", "DYN_THING"), ("OtherThing", "OTHER_THING")])
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        assert_eq!(
+            error(parse_static(quote! { version = "latest"; version = "latest"; })),
+            "duplicate key `version`"
+        );
+        assert_eq!(
+            error(parse_static(quote! { "version": "latest", "version": "latest" })),
+            "duplicate key `version`"
+        );
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let message = error(parse_static(quote! { verison = "latest"; }));
+
+        assert!(message.starts_with("unknown key `verison`, expected one of `version`"));
+    }
+
+    #[test]
+    fn conflicting_sources() {
+        assert_eq!(
+            error(parse_static(quote! { version = "latest"; binary = "Studio.exe"; })),
+            "`binary` can't be combined with `version`"
+        );
+    }
+
+    #[test]
+    fn settings_api_is_runtime_only() {
+        assert_eq!(
+            error(parse_static(quote! { settings_api = "https://mirror.example"; })),
+            "`settings_api` is only used by include_fflags_runtime!"
+        );
+    }
+}
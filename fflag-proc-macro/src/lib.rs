@@ -3,69 +3,316 @@ extern crate proc_macro;
 mod api;
 mod cache;
 mod dump;
+mod error;
+mod history;
+mod ide;
+mod input;
+mod limits;
+mod lockfile;
+mod manifest;
+mod mapped;
+mod memo;
+mod snapshot;
 mod stream;
+mod suggest;
+mod zipdir;
 
 use dump::{FastVar, FastVarValue, FastVarValueType};
+use error::FFlagError;
+use input::{FlagEntry, Input};
+use mapped::MappedFile;
 use proc_macro::TokenStream;
-use serde::Deserialize;
 use std::collections::HashMap;
-use std::error::Error;
+use std::fs::File;
+use std::io::{self, Cursor};
+use std::path::PathBuf;
+use std::sync::Arc;
 use quote::quote;
+use syn::parse::Parser;
 use syn::{Type, Ident};
 
-// static fflag proc macro
-fn default_version() -> String {
-    "latest".into()
+// loads the dumped fflags cached under `key`, dumping the binary when they aren't cached yet
+fn load_fflags<F>(key: &str, get_binary: F) -> Result<HashMap<String, FastVar>, FFlagError>
+where
+    F: FnOnce() -> Result<MappedFile, FFlagError>,
+{
+    // the ide only reads what a build already cached, without waiting on a build that is dumping
+    if ide::is_enabled() {
+        return cache::get_fflags_if_version_cached(key)?.ok_or_else(|| ide::not_cached_error(key));
+    }
+
+    let _lock = cache::lock_version(key)?;
+
+    let flags = match cache::get_fflags_if_version_cached(key)? {
+        Some(flags) => flags,
+        None => {
+            let binary = get_binary()?;
+            let mut flags: HashMap<String, FastVar> = dump::get_fflags(&binary)?
+                .iter()
+                .map(|flag| (flag.name.clone(), flag.clone()))
+                .collect();
+
+            cache::write_flags_to_cache(key, &mut flags)?;
+
+            flags
+        }
+    };
+
+    Ok(flags)
 }
 
-#[derive(Deserialize)]
-struct Input {
-    #[serde(default = "default_version")]
-    version: String,
+fn get_fflags(endpoints: &api::Endpoints, version: &str) -> Result<Arc<HashMap<String, FastVar>>, FFlagError> {
+    let key = endpoints.channel_key(version);
 
-    flags: HashMap<String, String>,
+    memo::flags(&key, || load_fflags(&key, || api::get_binary(endpoints, version.to_string())))
 }
 
-fn include_fflags_internal(item: TokenStream) -> Result<TokenStream, Box<dyn Error>> {
-    let str = format!("{{{}}}", item.to_string());
-    let input: Input = serde_json::from_str(str.as_str())?;
+// local files are cached by their contents, since they carry no version
+fn get_fflags_from_file(
+    path: PathBuf,
+    is_zip: bool,
+    span: proc_macro2::Span,
+) -> syn::Result<Arc<HashMap<String, FastVar>>> {
+    let key = format!("file:{}", path.display());
+
+    memo::flags(&key, || {
+        let local_error = |e: io::Error| FFlagError::LocalFile {
+            path: path.clone(),
+            reason: e.to_string(),
+        };
+
+        let file = File::open(&path).map_err(local_error)?;
+        let data = MappedFile::new(&file).map_err(local_error)?;
+
+        let cache_key = format!("file-{:08x}-{}", crc32fast::hash(&data), data.len());
+
+        load_fflags(&cache_key, || {
+            if is_zip {
+                api::unzip_binary(&path.display().to_string(), Cursor::new(&*data))
+            } else {
+                Ok(data)
+            }
+        })
+    })
+    .map_err(|e| syn::Error::new(span, format!("Failed to load fflags from {}: {}", path.display(), e)))
+}
+
+// an exact version is used as is, "latest" and deploy history specs resolve to one
+fn is_exact_version(spec: &str) -> bool {
+    spec != "latest" && !history::is_history_spec(spec)
+}
+
+// "latest" and deploy history specs stay pinned to the version in fflags.lock until the lock is updated
+fn resolve_version(endpoints: &api::Endpoints, spec: &str) -> Result<String, FFlagError> {
+    let locked_spec = endpoints.channel_key(spec);
+    if let Some(version) = lockfile::locked_version(&locked_spec)? {
+        return Ok(version);
+    }
+
+    // the ide falls back to whatever the last build resolved
+    if ide::is_enabled() {
+        return cache::read_resolved(&locked_spec)?.ok_or_else(|| ide::not_cached_error(spec));
+    }
+
+    match spec {
+        "latest" => api::get_latest_version(endpoints),
+        spec => {
+            let history = api::get_deploy_history(endpoints).map_err(|e| FFlagError::VersionSpec {
+                spec: String::from(spec),
+                reason: e.to_string(),
+            })?;
+
+            history::resolve(spec, &history)
+        }
+    }
+}
+
+// snapshots never touch the network, the version in the input only has to agree with them
+fn get_fflags_from_snapshot(
+    input: &Input,
+    path: PathBuf,
+    span: proc_macro2::Span,
+) -> syn::Result<Arc<HashMap<String, FastVar>>> {
+    let snapshot = memo::snapshot(&path, || snapshot::read(&path))
+        .map_err(|e| syn::Error::new(span, e.to_string()))?;
+
+    let spec = input.version();
+    if is_exact_version(&spec) && spec != snapshot.version {
+        return Err(syn::Error::new(
+            input.version_span(),
+            format!("Snapshot {} contains {}, not {}", path.display(), snapshot.version, spec),
+        ));
+    }
+
+    Ok(snapshot.flags.clone())
+}
+
+// fflags of an invocation, along with the files that should rebuild it when they change
+struct LoadedFlags {
+    flags: Arc<HashMap<String, FastVar>>,
+    tracked: Vec<PathBuf>,
+}
+
+// resolves the input version and loads its fflags, reporting failures at the version literal
+fn get_fflags_for_input(input: &Input) -> syn::Result<LoadedFlags> {
+    let snapshot_path = match &input.source {
+        Some(source) => Some((cache::manifest_relative_path(&source.value()), source.span())),
+        None => snapshot::env_snapshot_path().map(|path| (path, input.version_span())),
+    };
+
+    if let Some(binary) = &input.binary {
+        return Ok(LoadedFlags {
+            flags: get_fflags_from_file(cache::manifest_relative_path(&binary.value()), false, binary.span())?,
+            tracked: Vec::new(),
+        });
+    }
+
+    if let Some(zip) = &input.zip {
+        return Ok(LoadedFlags {
+            flags: get_fflags_from_file(cache::manifest_relative_path(&zip.value()), true, zip.span())?,
+            tracked: Vec::new(),
+        });
+    }
+
+    if let Some((path, span)) = snapshot_path {
+        return Ok(LoadedFlags {
+            flags: get_fflags_from_snapshot(input, path.clone(), span)?,
+            tracked: vec![path],
+        });
+    }
+
+    let span = input.version_span();
+    let spec = input.version();
+    let endpoints = api::Endpoints::new(
+        input.version_api.as_ref().map(|url| url.value()),
+        input.setup_cdn.as_ref().map(|url| url.value()),
+        input.channel.as_ref().map(|channel| channel.value()),
+    );
+    let locked_spec = endpoints.channel_key(&spec);
+
+    let is_exact = is_exact_version(&spec);
+
+    let version = match spec.as_str() {
+        "latest" => memo::version(&format!("{} {}", endpoints.version_api, locked_spec), || {
+            resolve_version(&endpoints, &spec)
+        })
+        .map_err(|e| {
+            syn::Error::new(span, format!("Failed to fetch latest version: {}", e))
+        })?,
+        spec if !is_exact => memo::version(&format!("{} {}", endpoints.setup_cdn, locked_spec), || {
+            resolve_version(&endpoints, spec)
+        })
+        .map_err(|e| syn::Error::new(span, e.to_string()))?,
+        version => version.to_string(),
+    };
+
+    let load_error = |e| syn::Error::new(span, format!("Failed to load fflags for {}: {}", version, e));
+
+    // ide expansions leave the lockfile, resolved versions and snapshots to real builds
+    let ide = ide::is_enabled();
+
+    let mut tracked = Vec::new();
+    let flags = if !is_exact && !ide {
+        let flags = get_fflags(&endpoints, &version).map_err(|e| match lockfile::locked_version(&locked_spec) {
+            Ok(Some(locked)) if locked == version => load_error(lockfile::unavailable_error(&version, e)),
+            _ => load_error(e),
+        })?;
 
-    let version = if input.version == "latest" {
-        api::get_latest_version()?
+        let names: Vec<String> = input.flags.iter().map(|flag| flag.name.value()).collect();
+        lockfile::record(&locked_spec, &version, &flags, &names).map_err(load_error)?;
+
+        tracked.extend(cache::record_resolved(&locked_spec, &version).map_err(load_error)?);
+        tracked.extend(lockfile::lockfile_path().filter(|path| path.exists()));
+        flags
     } else {
-        input.version
+        get_fflags(&endpoints, &version).map_err(load_error)?
     };
 
+    if !ide {
+        snapshot::write_if_requested(&version, &flags).map_err(load_error)?;
+    }
 
-    // read fflags if cached
-    let flags = match cache::get_fflags_if_version_cached(&version)? {
-        Some(flags) => flags,
-        None => {
-            let binary = api::get_binary(version.clone())?;
-            let mut flags: HashMap<String, FastVar> = dump::get_fflags(binary)?
-                .iter()
-                .map(|flag| (flag.name.clone(), flag.clone()))
-                .collect();
+    Ok(LoadedFlags { flags, tracked })
+}
 
-            cache::write_flags_to_cache(&version, &mut flags)?;
+// env vars that change what an expansion resolves to or writes
+const TRACKED_ENV: &[&str] = &[
+    "FFLAG_CACHE_DIR",
+    "FFLAG_CHANNEL",
+    "FFLAG_SETUP_CDN",
+    "FFLAG_SNAPSHOT",
+    "FFLAG_UPDATE_LOCK",
+    "FFLAG_VERSION_API",
+    "FFLAG_WRITE_SNAPSHOT",
+];
+
+// a stable proc macro can't declare its inputs, but rustc records the files read by include_bytes!
+// and the env vars read by option_env! in the crate's dep-info, so cargo rebuilds when they change
+fn rebuild_triggers(tracked: &[PathBuf]) -> proc_macro2::TokenStream {
+    let files = tracked.iter().filter_map(|path| path.to_str());
+    let vars = TRACKED_ENV.iter();
 
-            flags
-        }
+    quote! {
+        #(const _: &[u8] = include_bytes!(#files);)*
+        #(const _: Option<&str> = option_env!(#vars);)*
+    }
+}
+
+fn unknown_flag_error(entry: &FlagEntry, flags: &HashMap<String, FastVar>) -> syn::Error {
+    let real_name = entry.name.value();
+    let message = match suggest::suggest(&real_name, flags) {
+        Some(hint) => format!("Failed to find flag {} in binary, {}", real_name, hint),
+        None => format!("Failed to find flag {} in binary", real_name),
     };
 
+    syn::Error::new(entry.name.span(), message)
+}
+
+fn push_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+// static fflag proc macro
+fn include_fflags_internal(item: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let input = Input::parse_static.parse(item)?;
+    let loaded = get_fflags_for_input(&input)?;
+    let flags = &loaded.flags;
 
+    let mut tokens = rebuild_triggers(&loaded.tracked);
+    let mut errors = None;
 
-    let mut tokens = quote! {};
-    for (real_name, var_name) in input.flags {
+    for entry in input.flags {
+        let real_name = entry.name.value();
         let flag = match flags.get(&real_name) {
             Some(flag) => flag,
-            None => panic!("Failed to find flag {} in binary", real_name),
+            None => {
+                push_error(&mut errors, unknown_flag_error(&entry, flags));
+                continue;
+            }
         };
 
-        let var_name = Ident::new(&var_name, proc_macro2::Span::call_site());
+        let var_name = entry.var;
         let token = match flag.value.clone() {
-            FastVarValue::Invalid => panic!("Invalid FastVarValue"),
-            FastVarValue::Uninit => panic!("Dynamic FastVar {} must be loaded via runtime macro", flag.name),
+            FastVarValue::Invalid => {
+                push_error(
+                    &mut errors,
+                    syn::Error::new(entry.name.span(), format!("FastVar {} has an invalid value", real_name)),
+                );
+                continue;
+            }
+            FastVarValue::Uninit => {
+                push_error(
+                    &mut errors,
+                    syn::Error::new(
+                        entry.name.span(),
+                        format!("Dynamic FastVar {} must be loaded via include_fflags_runtime!", real_name),
+                    ),
+                );
+                continue;
+            }
 
             FastVarValue::Flag(flag) => quote! { 
                 pub const #var_name: bool = #flag;
@@ -78,20 +325,30 @@ fn include_fflags_internal(item: TokenStream) -> Result<TokenStream, Box<dyn Err
             },
             FastVarValue::String(str) => quote! {
                 pub const #var_name: &str = #str;
+            },
+            FastVarValue::Bytes(bytes) => {
+                let bytes = proc_macro2::Literal::byte_string(&bytes);
+
+                quote! {
+                    pub const #var_name: &[u8] = #bytes;
+                }
             }
         };
 
         tokens.extend(token);
     }
 
-    Ok(TokenStream::from(tokens))
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(tokens),
+    }
 }
 
 #[proc_macro]
 pub fn include_fflags(item: TokenStream) -> TokenStream {
     match include_fflags_internal(item) {
-        Ok(stream) => stream,
-        Err(e) => panic!("{}", e),
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
     }
 }
 
@@ -114,10 +371,9 @@ fn generate_base_flag_for_type(
     token_type: Type, 
     flag: &FastVar,
     real_name: String, 
-    var_name: String
+    token_name: Ident
 ) -> proc_macro2::TokenStream {
     let type_name = get_type_str_for_fast_var_value_type(flag.value_type);
-    let token_name = Ident::new(&var_name, proc_macro2::Span::call_site());
     let token_prefix = flag.get_full_name();
 
     quote! {
@@ -138,53 +394,54 @@ fn generate_base_flag_for_type(
     }
 }
 
-fn include_fflags_runtime_internal(item: TokenStream) -> Result<TokenStream, Box<dyn Error>> {
-    let str = format!("{{{}}}", item.to_string());
-    let map: HashMap<String, String> = serde_json::from_str(str.as_str())?;
+fn include_fflags_runtime_internal(item: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let input = Input::parse_runtime.parse(item)?;
+    let loaded = get_fflags_for_input(&input)?;
+    let flags = &loaded.flags;
 
-    // read fflags if cached
-    let version = api::get_latest_version()?;
-    let flags = match cache::get_fflags_if_version_cached(&version)? {
-        Some(flags) => flags,
-        None => {
-            let binary = api::get_binary(version.clone())?;
-            let mut flags: HashMap<String, FastVar> = dump::get_fflags(binary)?
-                .iter()
-                .map(|flag| (flag.name.clone(), flag.clone()))
-                .collect();
-
-            cache::write_flags_to_cache(&version, &mut flags)?;
-
-            flags
-        }
+    // without `settings_api` / `channel` keys the generated code reads FFLAG_SETTINGS_API / FFLAG_CHANNEL when it runs
+    let settings_api = match &input.settings_api {
+        Some(url) => quote! { Some(#url) },
+        None => quote! { None },
+    };
+    let channel = match &input.channel {
+        Some(channel) => quote! { Some(#channel) },
+        None => quote! { None },
     };
+    let get_dynamic_flags = quote! { api::get_dynamic_flags_with(#settings_api, #channel) };
 
     let mut tokens = quote! {
         static ref FLAGS_INTERNAL_DO_NOT_USE: api::ClientSettings = {
-            match api::get_dynamic_flags() {
+            match #get_dynamic_flags {
                 Ok(flags) => flags,
                 Err(e) => panic!("Failed to load fflags from api: {}", e)
             } 
         };
     };
+    let mut errors = None;
 
-    for (real_name, var_name) in map {
-        if real_name == "" { continue };
-
+    for entry in input.flags {
+        let real_name = entry.name.value();
         let flag = match flags.get(&real_name) {
             Some(flag) => flag,
-            None => panic!("FFlag {} not found in binary", real_name)
-        };
-
-        let token_type = match syn::parse_str(get_type_str_for_fast_var_value_type(flag.value_type)) {
-            Ok(t) => t,
-            Err(_) => panic!("Failed to parse syn token")
+            None => {
+                push_error(&mut errors, unknown_flag_error(&entry, flags));
+                continue;
+            }
         };
 
-        let token_name = Ident::new(&var_name, proc_macro2::Span::call_site());
+        let token_name = entry.var;
         let token_prefix = flag.get_full_name();
-        let token = if flag.value_type == FastVarValueType::String {
-            quote! {
+        let token = match flag.value_type {
+            FastVarValueType::Invalid | FastVarValueType::Uninit => {
+                push_error(
+                    &mut errors,
+                    syn::Error::new(entry.name.span(), format!("FFlag {} has an unknown value type", real_name)),
+                );
+                continue;
+            }
+
+            FastVarValueType::String => quote! {
                 pub static ref #token_name: &str = {
                     match FLAGS_INTERNAL_DO_NOT_USE.application_settings.get(&#token_prefix) {
                         Some(flag) => flag,
@@ -194,32 +451,41 @@ fn include_fflags_runtime_internal(item: TokenStream) -> Result<TokenStream, Box
                         }
                     }.as_str()
                 };
+            },
+
+            value_type => {
+                let token_type = syn::parse_str(get_type_str_for_fast_var_value_type(value_type))?;
+
+                generate_base_flag_for_type(token_type, &flag, real_name, token_name)
             }
-        } else if flag.value_type != FastVarValueType::Invalid && flag.value_type != FastVarValueType::Uninit {
-            generate_base_flag_for_type(token_type, &flag, real_name, var_name)
-        } else {
-            quote! {}
         };
         
         tokens.extend(token);
     }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
    
+    let triggers = rebuild_triggers(&loaded.tracked);
     let code = quote! {
         use fflag_macro::{api, lazy_static};
         use lazy_static::lazy_static;
 
+        #triggers
+
         lazy_static! {
             #tokens
         }
     };
 
-    Ok(TokenStream::from(code))
+    Ok(code)
 }
 
 #[proc_macro]
 pub fn include_fflags_runtime(item: TokenStream) -> TokenStream {
     match include_fflags_runtime_internal(item) {
-        Ok(stream) => stream,
-        Err(e) => panic!("{}", e),
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,93 @@
+use crate::error::FFlagError;
+use fflag_common::config::env_value;
+
+// studio zips are a few hundred MB and the exe inflates to about twice that,
+// the defaults leave plenty of room while still stopping a runaway mirror
+const DEFAULT_MAX_DOWNLOAD_SIZE: u64 = 1 << 30;
+const DEFAULT_MAX_UNPACKED_SIZE: u64 = 2 << 30;
+const DEFAULT_MAX_COMPRESSION_RATIO: u64 = 100;
+
+fn size_limit_error(name: &str, size: u64, limit: u64, var: &str) -> FFlagError {
+    FFlagError::SizeLimit {
+        name: String::from(name),
+        reason: format!("{} bytes is more than the {} bytes allowed by {}", size, limit, var),
+    }
+}
+
+pub fn max_download_size() -> u64 {
+    env_value("FFLAG_MAX_DOWNLOAD_SIZE").unwrap_or(DEFAULT_MAX_DOWNLOAD_SIZE)
+}
+
+pub fn check_download(name: &str, size: u64) -> Result<(), FFlagError> {
+    let limit = max_download_size();
+
+    match size > limit {
+        true => Err(size_limit_error(name, size, limit, "FFLAG_MAX_DOWNLOAD_SIZE")),
+        false => Ok(()),
+    }
+}
+
+// the smaller of FFLAG_MAX_UNPACKED_SIZE and `compressed` times FFLAG_MAX_COMPRESSION_RATIO, along with
+// the var that set it. `compressed` is the size of the entry in the archive, a zip bomb inflates far beyond it
+fn unpacked_limit(compressed: u64) -> (u64, &'static str) {
+    let max_size = env_value("FFLAG_MAX_UNPACKED_SIZE").unwrap_or(DEFAULT_MAX_UNPACKED_SIZE);
+    let ratio = env_value("FFLAG_MAX_COMPRESSION_RATIO").unwrap_or(DEFAULT_MAX_COMPRESSION_RATIO);
+    let ratio_limit = compressed.max(1).saturating_mul(ratio);
+
+    match ratio_limit < max_size {
+        true => (ratio_limit, "FFLAG_MAX_COMPRESSION_RATIO"),
+        false => (max_size, "FFLAG_MAX_UNPACKED_SIZE"),
+    }
+}
+
+pub fn max_unpacked_size(compressed: u64) -> u64 {
+    unpacked_limit(compressed).0
+}
+
+pub fn check_unpacked(name: &str, size: u64, compressed: u64) -> Result<(), FFlagError> {
+    let (limit, var) = unpacked_limit(compressed);
+
+    match size > limit {
+        true => Err(size_limit_error(name, size, limit, var)),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit_var(result: Result<(), FFlagError>) -> String {
+        match result {
+            Err(FFlagError::SizeLimit { reason, .. }) => reason.rsplit(' ').next().unwrap_or_default().to_string(),
+            _ => panic!("expected a size limit error"),
+        }
+    }
+
+    #[test]
+    fn ratio_limits_small_entries() {
+        assert!(check_unpacked("entry", 1000, 10).is_ok());
+        assert_eq!(limit_var(check_unpacked("entry", 1001, 10)), "FFLAG_MAX_COMPRESSION_RATIO");
+    }
+
+    #[test]
+    fn stored_empty_entries_count_as_one_byte() {
+        assert!(check_unpacked("entry", DEFAULT_MAX_COMPRESSION_RATIO, 0).is_ok());
+        assert_eq!(
+            limit_var(check_unpacked("entry", DEFAULT_MAX_COMPRESSION_RATIO + 1, 0)),
+            "FFLAG_MAX_COMPRESSION_RATIO"
+        );
+    }
+
+    #[test]
+    fn absolute_cap_limits_large_entries() {
+        let compressed = DEFAULT_MAX_UNPACKED_SIZE / 2;
+
+        assert_eq!(max_unpacked_size(compressed), DEFAULT_MAX_UNPACKED_SIZE);
+        assert!(check_unpacked("entry", DEFAULT_MAX_UNPACKED_SIZE, compressed).is_ok());
+        assert_eq!(
+            limit_var(check_unpacked("entry", DEFAULT_MAX_UNPACKED_SIZE + 1, compressed)),
+            "FFLAG_MAX_UNPACKED_SIZE"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::cache;
+use crate::dump::{FastVar, FastVarValue};
+use crate::error::FFlagError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const LOCKFILE: &str = "fflags.lock";
+
+// flags maps the name of every flag an invocation used to a digest of its value
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct LockEntry {
+    version: String,
+    #[serde(default)]
+    flags: BTreeMap<String, String>,
+}
+
+// keyed by the version as written in the macro input, e.g. "latest"
+#[derive(Serialize, Deserialize, Default)]
+struct Lockfile {
+    #[serde(default)]
+    versions: BTreeMap<String, LockEntry>,
+}
+
+pub fn lockfile_path() -> Option<PathBuf> {
+    cache::workspace_root().map(|root| root.join(LOCKFILE))
+}
+
+fn lockfile_error<E: ToString>(path: &Path, e: E) -> FFlagError {
+    FFlagError::Lockfile {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    }
+}
+
+// FFLAG_UPDATE_LOCK=1 re-resolves every locked version, like `cargo update`
+pub fn is_updating() -> bool {
+    env::var("FFLAG_UPDATE_LOCK").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn read_lockfile(path: &Path, file: &mut File) -> Result<Lockfile, FFlagError> {
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(|e| lockfile_error(path, e))?;
+
+    if text.trim().is_empty() {
+        return Ok(Lockfile::default());
+    }
+
+    serde_json::from_str(&text).map_err(|e| lockfile_error(path, e))
+}
+
+// crc of the value type and value as text, so it doesn't move with the cache format.
+// values the dumper couldn't read aren't locked, reading them later isn't a change
+fn digest(flag: &FastVar) -> Option<String> {
+    let value = match &flag.value {
+        FastVarValue::Int(val) => val.to_string().into_bytes(),
+        FastVarValue::Log(val) => val.to_string().into_bytes(),
+        FastVarValue::Flag(val) => val.to_string().into_bytes(),
+        FastVarValue::String(val) => val.clone().into_bytes(),
+        FastVarValue::Bytes(val) => val.clone(),
+        FastVarValue::Invalid | FastVarValue::Uninit => return None,
+    };
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(flag.value_type.as_ref().as_bytes());
+    hasher.update(b"=");
+    hasher.update(&value);
+
+    Some(format!("{:08x}", hasher.finalize()))
+}
+
+pub fn locked_version(spec: &str) -> Result<Option<String>, FFlagError> {
+    let path = match lockfile_path() {
+        Some(path) if !is_updating() => path,
+        _ => return Ok(None),
+    };
+
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(lockfile_error(&path, e)),
+    };
+
+    file.lock_shared().map_err(|e| lockfile_error(&path, e))?;
+
+    let lockfile = read_lockfile(&path, &mut file)?;
+    Ok(lockfile.versions.get(spec).map(|entry| entry.version.clone()))
+}
+
+// other invocations lock other flags of the same version, so the locked entry is merged into
+// unless the version moved, a flag that was locked with another digest fails unless `updating`
+fn merge_entry(
+    locked: Option<&LockEntry>,
+    version: &str,
+    digests: BTreeMap<String, String>,
+    updating: bool,
+) -> Result<LockEntry, String> {
+    let mut entry = match locked {
+        Some(locked) if locked.version == version => locked.clone(),
+        _ => LockEntry {
+            version: String::from(version),
+            flags: BTreeMap::new(),
+        },
+    };
+
+    for (name, digest) in digests {
+        match entry.flags.get(&name) {
+            Some(locked_digest) if *locked_digest != digest && !updating => {
+                return Err(format!(
+                    "flag {} of {} no longer matches the locked digest {} (got {}), set FFLAG_UPDATE_LOCK=1 to accept it",
+                    name, version, locked_digest, digest
+                ))
+            }
+            _ => {
+                entry.flags.insert(name, digest);
+            }
+        }
+    }
+
+    Ok(entry)
+}
+
+fn open_for_update(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+}
+
+fn write_lockfile(file: &mut File, lockfile: &Lockfile) -> std::io::Result<()> {
+    let text = serde_json::to_string_pretty(lockfile)?;
+
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(text.as_bytes())
+}
+
+// pins `spec` to `version` along with the values of the flags in `names`, failing if one of them
+// was already pinned with a different value. only the package cargo was asked to build records,
+// dependencies and read-only checkouts are still checked against an existing lockfile
+pub fn record(
+    spec: &str,
+    version: &str,
+    flags: &HashMap<String, FastVar>,
+    names: &[String],
+) -> Result<(), FFlagError> {
+    let path = match lockfile_path() {
+        Some(path) if env::var_os("CARGO_PRIMARY_PACKAGE").is_some() => path,
+        _ => return Ok(()),
+    };
+
+    let (mut file, writable) = match open_for_update(&path) {
+        Ok(file) => (file, true),
+        Err(_) => match File::open(&path) {
+            Ok(file) => (file, false),
+            Err(_) => return Ok(()),
+        },
+    };
+
+    if file.lock().is_err() {
+        return Ok(());
+    }
+
+    let mut lockfile = read_lockfile(&path, &mut file)?;
+    let locked = lockfile.versions.get(spec);
+
+    let digests = names
+        .iter()
+        .filter_map(|name| Some((name.clone(), digest(flags.get(name)?)?)))
+        .collect();
+    let entry = merge_entry(locked, version, digests, is_updating()).map_err(|e| lockfile_error(&path, e))?;
+
+    if !writable || locked == Some(&entry) {
+        return Ok(());
+    }
+
+    lockfile.versions.insert(String::from(spec), entry);
+
+    // a lockfile that can't be written just isn't updated, like a missing one
+    let _ = write_lockfile(&mut file, &lockfile);
+
+    Ok(())
+}
+
+// a locked version that can't be loaded anymore is an error instead of a silent re-resolve
+pub fn unavailable_error(version: &str, e: FFlagError) -> FFlagError {
+    let path = lockfile_path().unwrap_or_else(|| PathBuf::from(LOCKFILE));
+
+    lockfile_error(
+        &path,
+        format!(
+            "locked version {} could not be loaded ({}), set FFLAG_UPDATE_LOCK=1 to lock a new version",
+            version, e
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(version: &str, flags: &[(&str, &str)]) -> LockEntry {
+        LockEntry {
+            version: String::from(version),
+            flags: digests(flags),
+        }
+    }
+
+    fn digests(flags: &[(&str, &str)]) -> BTreeMap<String, String> {
+        flags
+            .iter()
+            .map(|(name, digest)| (String::from(*name), String::from(*digest)))
+            .collect()
+    }
+
+    #[test]
+    fn merges_new_flags_into_the_locked_entry() {
+        let locked = entry("version-a", &[("Foo", "00000001")]);
+
+        let merged = merge_entry(Some(&locked), "version-a", digests(&[("Bar", "00000002")]), false).unwrap();
+
+        assert_eq!(merged, entry("version-a", &[("Foo", "00000001"), ("Bar", "00000002")]));
+    }
+
+    #[test]
+    fn changed_digests_fail() {
+        let locked = entry("version-a", &[("Foo", "00000001")]);
+
+        let e = merge_entry(Some(&locked), "version-a", digests(&[("Foo", "00000002")]), false).unwrap_err();
+
+        assert!(e.contains("Foo") && e.contains("FFLAG_UPDATE_LOCK"));
+    }
+
+    #[test]
+    fn updating_accepts_changed_digests() {
+        let locked = entry("version-a", &[("Foo", "00000001"), ("Bar", "00000002")]);
+
+        let merged = merge_entry(Some(&locked), "version-a", digests(&[("Foo", "00000003")]), true).unwrap();
+
+        assert_eq!(merged, entry("version-a", &[("Foo", "00000003"), ("Bar", "00000002")]));
+    }
+
+    #[test]
+    fn new_versions_start_a_new_entry() {
+        let locked = entry("version-a", &[("Foo", "00000001")]);
+
+        let merged = merge_entry(Some(&locked), "version-b", digests(&[("Foo", "00000002")]), false).unwrap();
+
+        assert_eq!(merged, entry("version-b", &[("Foo", "00000002")]));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::error::FFlagError;
+
+const MANIFEST_HEADER: &str = "v0";
+
+// one package of a studio deployment as listed in {version}-rbxPkgManifest.txt
+pub struct Package {
+    pub name: String,
+    pub md5: String,
+    pub zip_size: u64,
+}
+
+pub struct Manifest {
+    pub packages: Vec<Package>,
+}
+
+fn manifest_error<E: ToString>(version: &str, e: E) -> FFlagError {
+    FFlagError::Manifest {
+        version: String::from(version),
+        reason: e.to_string(),
+    }
+}
+
+fn integrity_error<E: ToString>(package: &str, e: E) -> FFlagError {
+    FFlagError::PackageIntegrity {
+        package: String::from(package),
+        reason: e.to_string(),
+    }
+}
+
+impl Manifest {
+    // `v0` followed by a name, md5, zip size and unpacked size line for every package
+    pub fn parse(version: &str, text: &str) -> Result<Self, FFlagError> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        match lines.next() {
+            Some(MANIFEST_HEADER) => {}
+            Some(header) => return Err(manifest_error(version, format!("unsupported manifest version {}", header))),
+            None => return Err(manifest_error(version, "manifest is empty")),
+        }
+
+        let lines: Vec<&str> = lines.collect();
+        if !lines.len().is_multiple_of(4) {
+            return Err(manifest_error(version, "manifest ends in the middle of a package"));
+        }
+
+        let mut packages = Vec::new();
+        for chunk in lines.chunks(4) {
+            let size = |line: &str| {
+                line.parse::<u64>()
+                    .map_err(|e| manifest_error(version, format!("invalid size {} for {}: {}", line, chunk[0], e)))
+            };
+
+            // the unpacked size isn't needed, but a malformed one means a malformed manifest
+            size(chunk[3])?;
+
+            packages.push(Package {
+                name: String::from(chunk[0]),
+                md5: chunk[1].to_lowercase(),
+                zip_size: size(chunk[2])?,
+            });
+        }
+
+        Ok(Manifest { packages })
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Package> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+}
+
+impl Package {
+    // a corrupted or tampered download must never end up as compile time constants
+    pub fn verify(&self, size: u64, md5: &str) -> Result<(), FFlagError> {
+        if size != self.zip_size {
+            return Err(integrity_error(
+                &self.name,
+                format!("expected {} bytes, downloaded {}", self.zip_size, size),
+            ));
+        }
+
+        if md5 != self.md5 {
+            return Err(integrity_error(
+                &self.name,
+                format!("expected md5 {}, downloaded {}", self.md5, md5),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "v0\r\n\
+        RobloxStudio.zip\r\n\
+        8C6A1F3E0D9B4E7A2C5D1F0B3A6E9C4D\r\n\
+        1024\r\n\
+        4096\r\n\
+        shaders.zip\r\n\
+        0123456789abcdef0123456789abcdef\r\n\
+        10\r\n\
+        20\r\n";
+
+    fn error(result: Result<Manifest, FFlagError>) -> FFlagError {
+        match result {
+            Ok(_) => panic!("expected the manifest to be rejected"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn packages() {
+        let manifest = Manifest::parse("version-test", MANIFEST).unwrap();
+        let names: Vec<&str> = manifest.packages.iter().map(|package| package.name.as_str()).collect();
+
+        assert_eq!(names, ["RobloxStudio.zip", "shaders.zip"]);
+
+        let studio = manifest.find("RobloxStudio.zip").unwrap();
+        assert_eq!(studio.md5, "8c6a1f3e0d9b4e7a2c5d1f0b3a6e9c4d");
+        assert_eq!(studio.zip_size, 1024);
+
+        assert!(manifest.find("missing.zip").is_none());
+    }
+
+    #[test]
+    fn wrong_header() {
+        let e = error(Manifest::parse("version-test", "v1\nRobloxStudio.zip\nabc\n1\n2\n"));
+
+        assert!(matches!(e, FFlagError::Manifest { .. }));
+        assert!(e.to_string().contains("unsupported manifest version v1"));
+
+        assert!(error(Manifest::parse("version-test", "")).to_string().contains("manifest is empty"));
+    }
+
+    #[test]
+    fn truncated_package() {
+        let e = error(Manifest::parse("version-test", "v0\nRobloxStudio.zip\nabc\n1024\n"));
+
+        assert!(e.to_string().contains("ends in the middle of a package"));
+    }
+
+    #[test]
+    fn bad_sizes() {
+        let zip_size = error(Manifest::parse("version-test", "v0\nRobloxStudio.zip\nabc\n-1\n2\n"));
+        let unpacked_size = error(Manifest::parse("version-test", "v0\nRobloxStudio.zip\nabc\n1\nlots\n"));
+
+        assert!(zip_size.to_string().contains("invalid size -1 for RobloxStudio.zip"));
+        assert!(unpacked_size.to_string().contains("invalid size lots for RobloxStudio.zip"));
+    }
+
+    #[test]
+    fn verify() {
+        let manifest = Manifest::parse("version-test", MANIFEST).unwrap();
+        let studio = manifest.find("RobloxStudio.zip").unwrap();
+
+        assert!(studio.verify(1024, "8c6a1f3e0d9b4e7a2c5d1f0b3a6e9c4d").is_ok());
+
+        let size = studio.verify(1023, "8c6a1f3e0d9b4e7a2c5d1f0b3a6e9c4d").unwrap_err();
+        assert!(matches!(size, FFlagError::PackageIntegrity { .. }));
+        assert!(size.to_string().contains("expected 1024 bytes, downloaded 1023"));
+
+        let md5 = studio.verify(1024, "00000000000000000000000000000000").unwrap_err();
+        assert!(matches!(md5, FFlagError::PackageIntegrity { .. }));
+        assert!(md5.to_string().contains("expected md5 8c6a1f3e0d9b4e7a2c5d1f0b3a6e9c4d"));
+    }
+}
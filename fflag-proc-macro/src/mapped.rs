@@ -0,0 +1,71 @@
+use crate::error::FFlagError;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Deref;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// a file mapped into memory, so a studio binary is dumped without copying it onto the heap
+pub struct MappedFile {
+    map: Mmap,
+}
+
+pub fn temp_file_error<E: ToString>(e: E) -> FFlagError {
+    FFlagError::TempFile {
+        reason: e.to_string(),
+    }
+}
+
+impl MappedFile {
+    // the file must not be truncated while it is mapped, which holds for our own
+    // anonymous temp files and is assumed for local studio builds
+    pub fn new(file: &File) -> io::Result<Self> {
+        let map = unsafe { Mmap::map(file)? };
+
+        Ok(MappedFile { map })
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+pub fn temp_file() -> Result<File, FFlagError> {
+    tempfile::tempfile().map_err(temp_file_error)
+}
+
+// replaces the contents of `file` with `reader` in chunks, handing every chunk to `inspect` on the way
+pub fn copy_with<R, F>(reader: &mut R, file: &mut File, mut inspect: F) -> io::Result<u64>
+where
+    R: Read,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut size = 0;
+
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        inspect(&buf[..read])?;
+        file.write_all(&buf[..read])?;
+        size += read as u64;
+    }
+
+    file.flush()?;
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok(size)
+}
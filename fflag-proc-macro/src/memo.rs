@@ -0,0 +1,82 @@
+use crate::dump::FastVar;
+use crate::error::FFlagError;
+use crate::ide;
+use crate::snapshot::Snapshot;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+
+type FlagMap = HashMap<String, FastVar>;
+
+// every invocation in a rustc process shares these, so a crate with many
+// include_fflags! calls only resolves and loads each version once
+static VERSIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+static FLAGS: OnceLock<Mutex<HashMap<String, Arc<FlagMap>>>> = OnceLock::new();
+static SNAPSHOTS: OnceLock<Mutex<HashMap<PathBuf, (Option<SystemTime>, Arc<Snapshot>)>>> = OnceLock::new();
+
+// a panic in another invocation must not take every later one down with it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// resolved versions are keyed by the spec and the endpoint that resolved it, since a mirror can lag behind roblox.
+// rust-analyzer keeps its process alive across builds that move the lock, so it always resolves again,
+// which only reads fflags.lock and the cache there
+pub fn version<F>(key: &str, resolve: F) -> Result<String, FFlagError>
+where
+    F: FnOnce() -> Result<String, FFlagError>,
+{
+    if ide::is_enabled() {
+        return resolve();
+    }
+
+    let mut versions = lock(VERSIONS.get_or_init(|| Mutex::new(HashMap::new())));
+
+    if let Some(version) = versions.get(key) {
+        return Ok(version.clone());
+    }
+
+    let version = resolve()?;
+    versions.insert(String::from(key), version.clone());
+
+    Ok(version)
+}
+
+pub fn flags<F>(version: &str, load: F) -> Result<Arc<FlagMap>, FFlagError>
+where
+    F: FnOnce() -> Result<FlagMap, FFlagError>,
+{
+    let mut flags = lock(FLAGS.get_or_init(|| Mutex::new(HashMap::new())));
+
+    if let Some(flags) = flags.get(version) {
+        return Ok(flags.clone());
+    }
+
+    let loaded = Arc::new(load()?);
+    flags.insert(String::from(version), loaded.clone());
+
+    Ok(loaded)
+}
+
+// snapshots are reloaded once the file changes, for the same reason
+pub fn snapshot<F>(path: &Path, load: F) -> Result<Arc<Snapshot>, FFlagError>
+where
+    F: FnOnce() -> Result<Snapshot, FFlagError>,
+{
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let mut snapshots = lock(SNAPSHOTS.get_or_init(|| Mutex::new(HashMap::new())));
+
+    match snapshots.get(path) {
+        Some((loaded_modified, snapshot)) if modified.is_some() && *loaded_modified == modified => {
+            return Ok(snapshot.clone())
+        }
+        _ => {}
+    }
+
+    let loaded = Arc::new(load()?);
+    snapshots.insert(path.to_path_buf(), (modified, loaded.clone()));
+
+    Ok(loaded)
+}
@@ -0,0 +1,91 @@
+use crate::cache::{self, format};
+use crate::dump::FastVar;
+use crate::error::FFlagError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// json form of a snapshot, the binary form is a cache entry
+#[derive(Serialize, Deserialize)]
+struct JsonSnapshot {
+    version: String,
+    flags: Vec<FastVar>,
+}
+
+pub struct Snapshot {
+    pub version: String,
+    pub flags: Arc<HashMap<String, FastVar>>,
+}
+
+fn snapshot_error<E: ToString>(path: &Path, e: E) -> FFlagError {
+    FFlagError::Snapshot {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    }
+}
+
+// FFLAG_SNAPSHOT makes every invocation without an explicit source read from a snapshot
+pub fn env_snapshot_path() -> Option<PathBuf> {
+    env::var("FFLAG_SNAPSHOT")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(|path| cache::manifest_relative_path(&path))
+}
+
+pub fn read(path: &Path) -> Result<Snapshot, FFlagError> {
+    let data = fs::read(path).map_err(|e| snapshot_error(path, e))?;
+
+    if format::is_entry(&data) {
+        let (version, flags) = format::decode_entry(data).ok_or_else(|| {
+            snapshot_error(path, "binary snapshot is corrupt or from another format version")
+        })?;
+
+        return Ok(Snapshot {
+            version,
+            flags: Arc::new(flags),
+        });
+    }
+
+    let snapshot: JsonSnapshot = serde_json::from_slice(&data).map_err(|e| snapshot_error(path, e))?;
+
+    Ok(Snapshot {
+        version: snapshot.version,
+        flags: Arc::new(
+            snapshot
+                .flags
+                .into_iter()
+                .map(|flag| (flag.name.clone(), flag))
+                .collect(),
+        ),
+    })
+}
+
+// `.json` paths get a json snapshot, anything else the binary cache format
+pub fn write(path: &Path, version: &str, flags: &HashMap<String, FastVar>) -> Result<(), FFlagError> {
+    let data = if path.extension().is_some_and(|extension| extension == "json") {
+        let mut flags: Vec<FastVar> = flags.values().cloned().collect();
+        flags.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let snapshot = JsonSnapshot {
+            version: String::from(version),
+            flags,
+        };
+
+        serde_json::to_vec_pretty(&snapshot).map_err(|e| snapshot_error(path, e))?
+    } else {
+        format::encode(version, &mut flags.clone())?
+    };
+
+    fs::write(path, data).map_err(|e| snapshot_error(path, e))
+}
+
+// FFLAG_WRITE_SNAPSHOT=path dumps the flags loaded by the build into a snapshot file
+pub fn write_if_requested(version: &str, flags: &HashMap<String, FastVar>) -> Result<(), FFlagError> {
+    match env::var("FFLAG_WRITE_SNAPSHOT") {
+        Ok(path) if !path.is_empty() => write(&cache::manifest_relative_path(&path), version, flags),
+        _ => Ok(()),
+    }
+}
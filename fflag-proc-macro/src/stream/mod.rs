@@ -1,5 +1,5 @@
+use crate::error::FFlagError;
 use num_traits::NumCast;
-use std::error::Error;
 
 mod traits;
 pub use traits::*;
@@ -37,11 +37,19 @@ impl NetworkStream {
         self.data.append(&mut bytes.as_ref().to_vec());
     }
 
-    pub fn read_bytes<'a>(&mut self, num_bytes: usize) -> Result<Vec<u8>, &'a str> {
+    fn out_of_bounds(&self, num_bytes: usize) -> FFlagError {
+        FFlagError::StreamOutOfBounds {
+            offset: self.read_pointer,
+            len: num_bytes,
+            size: self.data.len(),
+        }
+    }
+
+    pub fn read_bytes(&mut self, num_bytes: usize) -> Result<Vec<u8>, FFlagError> {
         let slice = self
             .data
             .get(self.read_pointer..self.read_pointer + num_bytes)
-            .map_or(Err("Out of bounds read_bytes attempted"), |slice| Ok(slice))?;
+            .ok_or_else(|| self.out_of_bounds(num_bytes))?;
 
         self.read_pointer += num_bytes;
         Ok(slice.to_vec())
@@ -51,11 +59,11 @@ impl NetworkStream {
         self.data.append(&mut vec![byte]);
     }
 
-    pub fn read_byte<'a>(&mut self) -> Result<u8, &'a str> {
+    pub fn read_byte(&mut self) -> Result<u8, FFlagError> {
         let byte = self
             .data
             .get(self.read_pointer)
-            .map_or(Err("Out of bounds read_bytes attempted"), |slice| Ok(slice))?;
+            .ok_or_else(|| self.out_of_bounds(1))?;
 
         self.read_pointer += 1;
         Ok(*byte)
@@ -65,18 +73,18 @@ impl NetworkStream {
         self.write_byte(bool as u8);
     }
 
-    pub fn read_bool<'a>(&mut self) -> Result<bool, &'a str> {
+    pub fn read_bool(&mut self) -> Result<bool, FFlagError> {
         Ok(self.read_byte()? == 0x01)
     }
 
-    pub fn read_le<'a, T>(&mut self) -> Result<T, &'a str>
+    pub fn read_le<T>(&mut self) -> Result<T, FFlagError>
     where
         T: Bytes,
     {
         Ok(T::from_le_bytes(self.read_bytes(std::mem::size_of::<T>())?))
     }
 
-    pub fn read_be<'a, T>(&mut self) -> Result<T, &'a str>
+    pub fn read_be<T>(&mut self) -> Result<T, FFlagError>
     where
         T: Bytes,
     {
@@ -97,47 +105,59 @@ impl NetworkStream {
         self.write_bytes(T::to_be_bytes(val));
     }
 
-    pub fn write_string_le<'a, T>(&mut self, s: &str) -> Result<(), &'a str>
+    pub fn write_string_le<T>(&mut self, s: &str) -> Result<(), FFlagError>
     where
         T: Bytes + NumCast,
     {
-        self.write_le::<T>(T::from(s.len()).map_or(Err("Failed to cast string usize"), |d| Ok(d))?);
+        let len = T::from(s.len()).ok_or(FFlagError::StreamStringTooLong { len: s.len() })?;
+        self.write_le::<T>(len);
 
         self.write_bytes(s.as_bytes().to_vec());
         Ok(())
     }
 
-    pub fn read_string_le<'a, T>(&mut self) -> Result<String, Box<dyn Error>>
+    pub fn read_string_le<T>(&mut self) -> Result<String, FFlagError>
     where
         T: Bytes + NumCast,
     {
-        let len = <usize as NumCast>::from(self.read_le::<T>()?)
-            .map_or(Err("Failed to convert string size_t"), |d| Ok(d))?;
-
-        Ok(String::from_utf8(self.read_bytes(len)?)?)
+        let offset = self.read_pointer;
+        let len = <usize as NumCast>::from(self.read_le::<T>()?).ok_or(
+            FFlagError::StreamInvalidString {
+                offset,
+                reason: String::from("length does not fit in usize"),
+            },
+        )?;
+
+        self.read_sized_string(len)
     }
 
-    pub fn write_string_be<'a, T>(&mut self, s: &str) -> Result<(), &'a str>
+    pub fn write_string_be<T>(&mut self, s: &str) -> Result<(), FFlagError>
     where
         T: Bytes + NumCast,
     {
-        self.write_be::<T>(T::from(s.len()).map_or(Err("Failed to cast string usize"), |d| Ok(d))?);
+        let len = T::from(s.len()).ok_or(FFlagError::StreamStringTooLong { len: s.len() })?;
+        self.write_be::<T>(len);
 
         self.write_bytes(s.as_bytes().to_vec());
         Ok(())
     }
 
-    pub fn read_string_be<'a, T>(&mut self) -> Result<String, Box<dyn Error>>
+    pub fn read_string_be<T>(&mut self) -> Result<String, FFlagError>
     where
         T: Bytes + NumCast,
     {
-        let len = <usize as NumCast>::from(self.read_be::<T>()?)
-            .map_or(Err("Failed to convert string size_t"), |d| Ok(d))?;
-
-        Ok(String::from_utf8(self.read_bytes(len)?)?)
+        let offset = self.read_pointer;
+        let len = <usize as NumCast>::from(self.read_be::<T>()?).ok_or(
+            FFlagError::StreamInvalidString {
+                offset,
+                reason: String::from("length does not fit in usize"),
+            },
+        )?;
+
+        self.read_sized_string(len)
     }
 
-    pub fn read_varint64<'a>(&mut self) -> Result<u64, &'a str> {
+    pub fn read_varint64(&mut self) -> Result<u64, FFlagError> {
         let mut int: u64 = 0;
         let mut idx: u64 = 0;
 
@@ -155,13 +175,13 @@ impl NetworkStream {
         Ok(int)
     }
 
-    pub fn read_signedvarint64<'a>(&mut self) -> Result<i64, &'a str> {
+    pub fn read_signedvarint64(&mut self) -> Result<i64, FFlagError> {
         let int = self.read_varint64()?;
 
         Ok(-((int & 1) as i64) ^ ((int >> 1) as i64))
     }
 
-    pub fn read_varint32<'a>(&mut self) -> Result<u32, &'a str> {
+    pub fn read_varint32(&mut self) -> Result<u32, FFlagError> {
         let mut int: u32 = 0;
         let mut idx: u32 = 0;
 
@@ -179,52 +199,57 @@ impl NetworkStream {
         Ok(int)
     }
 
-    pub fn read_signedvarint32<'a>(&mut self) -> Result<i32, &'a str> {
+    pub fn read_signedvarint32(&mut self) -> Result<i32, FFlagError> {
         let int = self.read_varint32()?;
 
         Ok(-((int & 1) as i32) ^ ((int >> 1) as i32))
     }
 
-    pub fn read_sized_string(&mut self, size: usize) -> Result<String, Box<dyn Error>> {
-        Ok(String::from_utf8(self.read_bytes(size)?)?)
+    pub fn read_sized_string(&mut self, size: usize) -> Result<String, FFlagError> {
+        let offset = self.read_pointer;
+
+        String::from_utf8(self.read_bytes(size)?).map_err(|e| FFlagError::StreamInvalidString {
+            offset,
+            reason: e.to_string(),
+        })
     }
 
-    pub fn read_network_string64(&mut self) -> Result<String, Box<dyn Error>> {
+    pub fn read_network_string64(&mut self) -> Result<String, FFlagError> {
         let int = self.read_varint64()?;
 
         Ok(self.read_sized_string(int as usize)?)
     }
 
-    pub fn read_network_string32(&mut self) -> Result<String, Box<dyn Error>> {
+    pub fn read_network_string32(&mut self) -> Result<String, FFlagError> {
         let int = self.read_varint32()?;
 
         Ok(self.read_sized_string(int as usize)?)
     }
 
-    pub fn read_float32_be(&mut self) -> Result<f32, Box<dyn Error>> {
+    pub fn read_float32_be(&mut self) -> Result<f32, FFlagError> {
         Ok(f32::from_bits(self.read_be::<u32>()?))
     }
 
-    pub fn read_float32_le(&mut self) -> Result<f32, Box<dyn Error>> {
+    pub fn read_float32_le(&mut self) -> Result<f32, FFlagError> {
         Ok(f32::from_bits(self.read_le::<u32>()?))
     }
 
-    pub fn read_float64_be(&mut self) -> Result<f64, Box<dyn Error>> {
+    pub fn read_float64_be(&mut self) -> Result<f64, FFlagError> {
         Ok(f64::from_bits(self.read_be::<u64>()?))
     }
 
-    pub fn read_float64_le(&mut self) -> Result<f64, Box<dyn Error>> {
+    pub fn read_float64_le(&mut self) -> Result<f64, FFlagError> {
         Ok(f64::from_bits(self.read_le::<u64>()?))
     }
 
-    pub fn write<T>(&mut self, obj: &mut T) -> Result<(), Box<dyn Error>>
+    pub fn write<T>(&mut self, obj: &mut T) -> Result<(), FFlagError>
     where
         T: Serialize<T>,
     {
         obj.write(self)
     }
 
-    pub fn read<T>(&mut self) -> Result<T, Box<dyn Error>>
+    pub fn read<T>(&mut self) -> Result<T, FFlagError>
     where
         T: Serialize<T>,
     {
@@ -1,9 +1,9 @@
 use super::NetworkStream;
-use std::error::Error;
+use crate::error::FFlagError;
 
 pub trait Serialize<T> {
-    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), Box<dyn Error>>;
-    fn read(stream: &mut NetworkStream) -> Result<T, Box<dyn Error>>;
+    fn write(&mut self, stream: &mut NetworkStream) -> Result<(), FFlagError>;
+    fn read(stream: &mut NetworkStream) -> Result<T, FFlagError>;
 }
 
 pub trait Bytes {
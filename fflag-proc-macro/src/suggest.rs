@@ -0,0 +1,163 @@
+use crate::dump::FastVar;
+use std::collections::HashMap;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+// levenshtein distance, compared case insensitively
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}
+
+// names in the dump are stored without the `DFInt`/`FFlag`/... prefix
+fn find_by_full_name<'a>(name: &str, flags: &'a HashMap<String, FastVar>) -> Option<&'a FastVar> {
+    flags.values().find(|flag| flag.get_full_name() == name)
+}
+
+fn strip_prefix(name: &str) -> Option<(&str, &str)> {
+    for prefix in ["DFFlag", "DFInt", "DFLog", "DFString", "FFlag", "FInt", "FLog", "FString"] {
+        if let Some(bare) = name.strip_prefix(prefix) {
+            if !bare.is_empty() {
+                return Some((prefix, bare));
+            }
+        }
+    }
+
+    None
+}
+
+fn closest_names<'a>(name: &str, flags: &'a HashMap<String, FastVar>) -> Vec<&'a String> {
+    let max_distance = (name.len() / 3).max(2);
+
+    let mut candidates: Vec<(usize, &String)> = flags
+        .keys()
+        .filter(|key| key.len().abs_diff(name.len()) <= max_distance)
+        .map(|key| (edit_distance(name, key), key))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, key)| key)
+        .collect()
+}
+
+fn format_names(names: &[&String]) -> String {
+    let names: Vec<String> = names.iter().map(|name| format!("`{}`", name)).collect();
+
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+// builds a hint for a flag name that is missing from the dump
+pub fn suggest(name: &str, flags: &HashMap<String, FastVar>) -> Option<String> {
+    if let Some(flag) = find_by_full_name(name, flags) {
+        return Some(format!(
+            "flags are named without their prefix, did you mean `{}`?",
+            flag.name
+        ));
+    }
+
+    if let Some((prefix, bare)) = strip_prefix(name) {
+        if flags.contains_key(bare) {
+            return Some(format!(
+                "flags are named without their `{}` prefix, did you mean `{}`?",
+                prefix, bare
+            ));
+        }
+
+        let names = closest_names(bare, flags);
+        if !names.is_empty() {
+            return Some(format!(
+                "flags are named without their `{}` prefix, did you mean {}?",
+                prefix,
+                format_names(&names)
+            ));
+        }
+    }
+
+    let names = closest_names(name, flags);
+    if names.is_empty() {
+        return None;
+    }
+
+    Some(format!("did you mean {}?", format_names(&names)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::{FastVarType, FastVarValue, FastVarValueType};
+
+    fn flags(values: &[(&str, FastVarType, FastVarValueType)]) -> HashMap<String, FastVar> {
+        values
+            .iter()
+            .map(|&(name, var_type, value_type)| {
+                let flag = FastVar {
+                    name: String::from(name),
+                    var_type,
+                    value_type,
+                    value: FastVarValue::Uninit,
+                };
+
+                (String::from(name), flag)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prefixed_names_point_at_the_bare_name() {
+        let flags = flags(&[("Foo", FastVarType::Static, FastVarValueType::Flag)]);
+
+        let hint = suggest("FFlagFoo", &flags).unwrap();
+
+        assert_eq!(hint, "flags are named without their prefix, did you mean `Foo`?");
+    }
+
+    #[test]
+    fn wrong_prefixes_point_at_the_bare_name() {
+        let flags = flags(&[("Foo", FastVarType::Dynamic, FastVarValueType::Int)]);
+
+        let hint = suggest("FIntFoo", &flags).unwrap();
+
+        assert_eq!(hint, "flags are named without their `FInt` prefix, did you mean `Foo`?");
+    }
+
+    #[test]
+    fn typos_within_the_distance_limit() {
+        let flags = flags(&[
+            ("SomeFlag", FastVarType::Static, FastVarValueType::Flag),
+            ("OtherThing", FastVarType::Static, FastVarValueType::Flag),
+        ]);
+
+        assert_eq!(suggest("SomeFlga", &flags).unwrap(), "did you mean `SomeFlag`?");
+    }
+
+    #[test]
+    fn nothing_beyond_the_distance_limit() {
+        let flags = flags(&[("SomeFlag", FastVarType::Static, FastVarValueType::Flag)]);
+
+        assert_eq!(suggest("SomeThing", &flags), None);
+    }
+}
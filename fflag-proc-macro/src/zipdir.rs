@@ -0,0 +1,145 @@
+// just enough of the zip format to list the entries of a remote package from its tail
+
+const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+
+const END_OF_DIRECTORY_SIZE: usize = 22;
+const DIRECTORY_ENTRY_SIZE: usize = 46;
+const MAX_COMMENT_SIZE: usize = u16::MAX as usize;
+
+// the end of directory record sits in the last 22 bytes plus an optional comment
+pub const MAX_TAIL_SIZE: u64 = (END_OF_DIRECTORY_SIZE + MAX_COMMENT_SIZE) as u64;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + 2)?;
+
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 4)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64)
+}
+
+// offset and size of the central directory, zip64 archives aren't supported
+pub fn find_directory(tail: &[u8]) -> Option<(u64, u64)> {
+    let start = tail.len().checked_sub(END_OF_DIRECTORY_SIZE)?;
+    let record = (0..=start)
+        .rev()
+        .find(|&offset| tail[offset..].starts_with(END_OF_DIRECTORY))?;
+
+    let size = read_u32(tail, record + 12)?;
+    let offset = read_u32(tail, record + 16)?;
+
+    if size == u32::MAX as u64 || offset == u32::MAX as u64 {
+        return None;
+    }
+
+    Some((offset, size))
+}
+
+pub fn entry_names(directory: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut offset = 0;
+
+    // records come from a remote mirror, a truncated or malformed one just ends the listing
+    while directory.get(offset..).is_some_and(|rest| rest.starts_with(DIRECTORY_ENTRY)) {
+        let lengths = (
+            read_u16(directory, offset + 28),
+            read_u16(directory, offset + 30),
+            read_u16(directory, offset + 32),
+        );
+
+        let (Some(name_len), Some(extra_len), Some(comment_len)) = lengths else {
+            break;
+        };
+
+        let name_start = offset + DIRECTORY_ENTRY_SIZE;
+        let Some(name) = directory.get(name_start..name_start + name_len) else {
+            break;
+        };
+
+        names.push(String::from_utf8_lossy(name).into_owned());
+        offset = name_start + name_len + extra_len + comment_len;
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn end_of_directory(size: u32, offset: u32, comment: &[u8]) -> Vec<u8> {
+        let mut record = END_OF_DIRECTORY.to_vec();
+        record.extend_from_slice(&[0; 8]);
+        record.extend_from_slice(&size.to_le_bytes());
+        record.extend_from_slice(&offset.to_le_bytes());
+        record.extend_from_slice(&(comment.len() as u16).to_le_bytes());
+        record.extend_from_slice(comment);
+        record
+    }
+
+    fn directory_entry(name: &str, extra_len: u16) -> Vec<u8> {
+        let mut entry = DIRECTORY_ENTRY.to_vec();
+        entry.extend_from_slice(&[0; 24]);
+        entry.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        entry.extend_from_slice(&extra_len.to_le_bytes());
+        entry.extend_from_slice(&0u16.to_le_bytes());
+        entry.extend_from_slice(&[0; 12]);
+        entry.extend_from_slice(name.as_bytes());
+        entry
+    }
+
+    #[test]
+    fn directory_location() {
+        let mut tail = vec![0xAA; 100];
+        tail.extend(end_of_directory(300, 1000, b""));
+
+        assert_eq!(find_directory(&tail), Some((1000, 300)));
+    }
+
+    #[test]
+    fn directory_location_with_a_comment() {
+        let mut tail = vec![0xAA; 100];
+        tail.extend(end_of_directory(300, 1000, b"built by a mirror"));
+
+        assert_eq!(find_directory(&tail), Some((1000, 300)));
+    }
+
+    #[test]
+    fn zip64_sentinels() {
+        assert_eq!(find_directory(&end_of_directory(u32::MAX, 1000, b"")), None);
+        assert_eq!(find_directory(&end_of_directory(300, u32::MAX, b"")), None);
+    }
+
+    #[test]
+    fn missing_directory() {
+        assert_eq!(find_directory(&[0xAA; 100]), None);
+        assert_eq!(find_directory(END_OF_DIRECTORY), None);
+    }
+
+    #[test]
+    fn names() {
+        let mut directory = directory_entry("RobloxStudioBeta.exe", 0);
+        directory.extend(directory_entry("content/", 4));
+        directory.extend_from_slice(&[0; 4]);
+
+        assert_eq!(entry_names(&directory), ["RobloxStudioBeta.exe", "content/"]);
+    }
+
+    #[test]
+    fn truncated_directory() {
+        let mut directory = directory_entry("RobloxStudioBeta.exe", 100);
+        assert_eq!(entry_names(&directory), ["RobloxStudioBeta.exe"]);
+
+        directory = directory_entry("RobloxStudioBeta.exe", 0);
+        directory.truncate(DIRECTORY_ENTRY_SIZE + 4);
+        assert!(entry_names(&directory).is_empty());
+
+        directory = directory_entry("RobloxStudioBeta.exe", 0);
+        directory.truncate(20);
+        assert!(entry_names(&directory).is_empty());
+    }
+}
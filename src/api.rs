@@ -1,6 +1,9 @@
+use crate::error::FFlagError;
+use fflag_common::http;
 use serde::Deserialize;
-use std::error::Error;
+use serde::de::DeserializeOwned;
 use std::collections::HashMap;
+use std::env;
 
 
 #[derive(Deserialize)]
@@ -16,22 +19,71 @@ struct VersionData {
 }
 
 
-const DYNAMIC_FLAG_API: &str =
-    "https://clientsettingscdn.roblox.com/v2/settings/application/PCStudioApp";
+const DYNAMIC_FLAG_API: &str = "https://clientsettingscdn.roblox.com";
 
-const VERSION_API: &str =
-    "https://clientsettings.roblox.com/v2/client-version/WindowsStudio64/channel/LIVE";
+const VERSION_API: &str = "https://clientsettings.roblox.com";
 
+const LIVE_CHANNEL: &str = "LIVE";
 
+// FFLAG_SETTINGS_API / FFLAG_VERSION_API point the api at a mirror instead of roblox
+fn env_base_url(var: &str, default: &str) -> String {
+    let url = env::var(var)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| String::from(default));
 
-pub fn get_latest_version<'a>() -> Result<String, Box<dyn Error>> {
-    let res = attohttpc::get(VERSION_API).send()?;
-    let data: VersionData = serde_json::from_str(&res.text()?)?;
+    String::from(url.trim_end_matches('/'))
+}
+
+fn env_channel() -> Option<String> {
+    env::var("FFLAG_CHANNEL").ok().filter(|channel| !channel.is_empty())
+}
+
+
+fn get_json<T: DeserializeOwned>(url: &str) -> Result<T, FFlagError> {
+    let text = http::get_text(url)?;
+
+    serde_json::from_str(&text).map_err(|e| FFlagError::InvalidResponse {
+        url: String::from(url),
+        reason: e.to_string(),
+    })
+}
+
+pub fn get_latest_version() -> Result<String, FFlagError> {
+    let url = format!(
+        "{}/v2/client-version/WindowsStudio64/channel/{}",
+        env_base_url("FFLAG_VERSION_API", VERSION_API),
+        env_channel().unwrap_or_else(|| String::from(LIVE_CHANNEL))
+    );
+    let data: VersionData = get_json(&url)?;
 
     Ok(data.client_version_upload)
 }
 
-pub fn get_dynamic_flags() -> Result<ClientSettings, Box<dyn Error>> {
-    let res = attohttpc::get(DYNAMIC_FLAG_API).send()?;
-    Ok(serde_json::from_str(&res.text()?)?)
-}
\ No newline at end of file
+pub fn get_dynamic_flags() -> Result<ClientSettings, FFlagError> {
+    get_dynamic_flags_with(None, None)
+}
+
+pub fn get_dynamic_flags_from(base_url: &str) -> Result<ClientSettings, FFlagError> {
+    get_dynamic_flags_with(Some(base_url), None)
+}
+
+// arguments left as None fall back to FFLAG_SETTINGS_API / FFLAG_CHANNEL, channels other than LIVE read their bucket
+pub fn get_dynamic_flags_with(base_url: Option<&str>, channel: Option<&str>) -> Result<ClientSettings, FFlagError> {
+    let base_url = match base_url {
+        Some(base_url) => String::from(base_url.trim_end_matches('/')),
+        None => env_base_url("FFLAG_SETTINGS_API", DYNAMIC_FLAG_API),
+    };
+    let channel = channel.map(String::from).or_else(env_channel);
+
+    let url = match channel {
+        Some(channel) if !channel.eq_ignore_ascii_case(LIVE_CHANNEL) => format!(
+            "{}/v2/settings/application/PCStudioApp/bucket/{}",
+            base_url,
+            channel.to_lowercase()
+        ),
+        _ => format!("{}/v2/settings/application/PCStudioApp", base_url),
+    };
+
+    get_json(&url)
+}
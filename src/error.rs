@@ -0,0 +1,32 @@
+use fflag_common::HttpError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum FFlagError {
+    Network { url: String, reason: String },
+    InvalidResponse { url: String, reason: String },
+}
+
+impl fmt::Display for FFlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FFlagError::Network { url, reason } => {
+                write!(f, "Request to {} failed: {}", url, reason)
+            }
+            FFlagError::InvalidResponse { url, reason } => {
+                write!(f, "Invalid response from {}: {}", url, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FFlagError {}
+
+impl From<HttpError> for FFlagError {
+    fn from(e: HttpError) -> Self {
+        match e {
+            HttpError::Network { url, reason } => FFlagError::Network { url, reason },
+            HttpError::InvalidResponse { url, reason } => FFlagError::InvalidResponse { url, reason },
+        }
+    }
+}
@@ -2,4 +2,7 @@ extern crate fflag_proc_macro;
 
 pub use fflag_proc_macro::{include_fflags, include_fflags_runtime};
 pub use lazy_static;
-pub mod api;
\ No newline at end of file
+pub mod api;
+pub mod error;
+
+pub use error::FFlagError;
\ No newline at end of file
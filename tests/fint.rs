@@ -0,0 +1,33 @@
+use fflag_macro::include_fflags;
+
+include_fflags! {
+    source = "tests/fixtures/fint.json";
+
+    FIntNegative as NEGATIVE;
+    FIntZero as ZERO;
+    FIntMax as MAX;
+    FIntMin as MIN;
+    FIntUnsignedSnapshot as UNSIGNED_SNAPSHOT;
+}
+
+#[test]
+fn negative_default() {
+    assert_eq!(NEGATIVE, -1);
+    assert_eq!(MIN, i32::MIN);
+}
+
+#[test]
+fn zero_default() {
+    assert_eq!(ZERO, 0);
+}
+
+#[test]
+fn max_default() {
+    assert_eq!(MAX, i32::MAX);
+}
+
+// snapshots written while FInts were unsigned hold the bit pattern of negative defaults
+#[test]
+fn unsigned_snapshot_default() {
+    assert_eq!(UNSIGNED_SNAPSHOT, -1);
+}